@@ -1,7 +1,16 @@
-use crate::database::{DatabaseManager, Disease, Tag, Template, TemplateType};
+use super::float::{register_float_hotkey, restore_float};
+use crate::composition::SectionDependent;
+use crate::database::{
+    BulkOperationResult, DatabaseManager, DeleteError, Disease, SaveError, SectionRange,
+    SectionRef, Snippet, SnippetCategory, Tag, Template, TemplateType,
+};
+use crate::format::{FormatProfile, DEFAULT_PROFILE_ID};
+use crate::input::{AppPasteProfile, PasteStrategy};
+use crate::platform::history::{self, WindowExclusionRules};
+use crate::suggestion::{self, CompiledRules};
+use crate::validation::ValidationRules;
 use crate::AppState;
-use tauri::webview::WebviewWindowBuilder;
-use tauri::{AppHandle, State, WebviewUrl};
+use tauri::{AppHandle, State};
 
 /// 初始化数据库
 #[tauri::command]
@@ -9,24 +18,52 @@ pub async fn init_database(
     app_handle: AppHandle,
     state: State<'_, AppState>,
 ) -> Result<(), String> {
-    // 检查数据库是否已初始化
-    if state
+    // 检查数据库是否已初始化，初始化完成前一直持有锁，多个窗口同时调用时只初始化一次
+    let mut db = state
         .db
         .lock()
-        .map_err(|e| format!("Failed to lock database: {}", e))?
-        .is_some()
-    {
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    if db.is_some() {
         return Ok(());
     }
 
     match DatabaseManager::new(&app_handle) {
         Ok(db_manager) => {
+            // 应用保存的窗口排除规则
+            match db_manager.get_window_exclusion_rules() {
+                Ok(rules) => history::set_rules(rules),
+                Err(e) => log::warn!("读取窗口排除规则失败: {}", e),
+            }
+
+            // 编译保存的模板推荐规则
+            match db_manager.get_suggestion_rules() {
+                Ok(rules) => match CompiledRules::compile(&rules) {
+                    Ok(compiled) => suggestion::set_rules(compiled),
+                    Err(e) => log::warn!("模板推荐规则无效: {}", e),
+                },
+                Err(e) => log::warn!("读取模板推荐规则失败: {}", e),
+            }
+
+            let hotkey = db_manager.get_float_hotkey();
+
             // 将数据库管理器存储到应用状态中
-            let mut db = state
-                .db
-                .lock()
-                .map_err(|e| format!("Failed to lock database: {}", e))?;
             *db = Some(db_manager);
+            drop(db);
+
+            // 释放数据库锁后再注册保存的悬浮窗口快捷键，快捷键的回调也会访问数据库
+            match hotkey {
+                Ok(hotkey) => {
+                    if let Err(e) = register_float_hotkey(&app_handle, &hotkey) {
+                        log::warn!("注册悬浮窗口快捷键失败: {}", e);
+                    }
+                }
+                Err(e) => log::warn!("读取悬浮窗口快捷键失败: {}", e),
+            }
+
+            // 只在首次初始化时按保存的状态创建悬浮窗口
+            if let Err(e) = restore_float(&app_handle) {
+                log::warn!("创建悬浮窗口失败: {}", e);
+            }
             Ok(())
         }
         Err(e) => Err(format!("Failed to initialize database: {}", e)),
@@ -62,51 +99,122 @@ pub async fn get_template_by_id(
         .map_err(|e| format!("Database error: {}", e))
 }
 
-/// 保存模板
+/// 保存模板，返回由后端分配ID和时间戳后的模板
+///
+/// 模板已被其他窗口修改时返回 `conflict` 错误，其中携带服务端当前的模板。
 #[tauri::command]
 pub async fn save_template(
     template: Template,
     state: State<'_, AppState>,
-) -> Result<String, String> {
+) -> Result<Template, SaveError> {
     let mut db = state
         .db
         .lock()
         .map_err(|e| format!("Failed to lock database: {}", e))?;
     let db_manager = db.as_mut().ok_or("Database not initialized")?;
-    db_manager
-        .upsert_template(&template)
-        .map_err(|e| format!("Database error: {}", e))?;
-    Ok("Template saved successfully".to_string())
+    db_manager.save_template(template)
 }
 
-/// 导入模板
+/// 导入模板，任一模板未通过校验时不写入任何数据
 #[tauri::command]
 pub async fn import_templates(
     templates: Vec<Template>,
     state: State<'_, AppState>,
-) -> Result<String, String> {
+) -> Result<String, SaveError> {
     let mut db = state
         .db
         .lock()
         .map_err(|e| format!("Failed to lock database: {}", e))?;
     let db_manager = db.as_mut().ok_or("Database not initialized")?;
-    db_manager
-        .batch_upsert_templates(&templates)
-        .map_err(|e| format!("Database error: {}", e))?;
+    db_manager.import_templates(templates)?;
     Ok("Templates saved successfully".to_string())
 }
 
-/// 删除模板
+/// 复制模板，与拆分、合并相同，写入前校验副本（如追加“副本”后标题超长）
 #[tauri::command]
-pub async fn delete_template(id: String, state: State<'_, AppState>) -> Result<String, String> {
+pub async fn duplicate_template(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Template, SaveError> {
     let mut db = state
         .db
         .lock()
         .map_err(|e| format!("Failed to lock database: {}", e))?;
     let db_manager = db.as_mut().ok_or("Database not initialized")?;
-    db_manager
-        .delete_template(&id)
-        .map_err(|e| format!("Database error: {}", e))?;
+    let template = db_manager
+        .get_template_by_id(&id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or("Template not found")?;
+
+    let mut saved = db_manager.replace_templates(vec![template.duplicate()], &[])?;
+    Ok(saved.remove(0))
+}
+
+/// 按章节范围拆分模板
+#[tauri::command]
+pub async fn split_template(
+    id: String,
+    ranges: Vec<SectionRange>,
+    keep_original: bool,
+    state: State<'_, AppState>,
+) -> Result<Vec<Template>, SaveError> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    let template = db_manager
+        .get_template_by_id(&id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or("Template not found")?;
+
+    let parts = template.split(&ranges)?;
+    let remove_ids = if keep_original { vec![] } else { vec![id] };
+    db_manager.replace_templates(parts, &remove_ids)
+}
+
+/// 按指定章节顺序合并多个模板
+#[tauri::command]
+pub async fn merge_templates(
+    ids: Vec<String>,
+    section_order: Vec<SectionRef>,
+    title: String,
+    keep_originals: bool,
+    state: State<'_, AppState>,
+) -> Result<Template, SaveError> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+
+    let mut templates = Vec::with_capacity(ids.len());
+    for id in &ids {
+        let template = db_manager
+            .get_template_by_id(id)
+            .map_err(|e| format!("Database error: {}", e))?
+            .ok_or_else(|| format!("Template not found: {}", id))?;
+        templates.push(template);
+    }
+
+    let merged = Template::merge(&templates, &section_order, &title)?;
+    let remove_ids = if keep_originals { vec![] } else { ids };
+    let mut saved = db_manager.replace_templates(vec![merged], &remove_ids)?;
+    Ok(saved.remove(0))
+}
+
+/// 删除模板，仍被其他模板引用时拒绝删除
+#[tauri::command]
+pub async fn delete_template(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<String, DeleteError> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager.delete_template(&id)?;
     Ok("Template deleted successfully".to_string())
 }
 
@@ -127,6 +235,101 @@ pub async fn toggle_template_favorite(
     Ok("Template favorite status toggled successfully".to_string())
 }
 
+/// 批量删除模板
+#[tauri::command]
+pub async fn bulk_delete_templates(
+    ids: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<BulkOperationResult>, String> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .bulk_delete_templates(&ids)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 批量设置模板收藏状态
+#[tauri::command]
+pub async fn bulk_set_template_favorite(
+    ids: Vec<String>,
+    is_favorite: bool,
+    state: State<'_, AppState>,
+) -> Result<Vec<BulkOperationResult>, String> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .bulk_set_favorite(&ids, is_favorite)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 批量设置模板疾病分类
+#[tauri::command]
+pub async fn bulk_set_template_disease(
+    ids: Vec<String>,
+    disease: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<BulkOperationResult>, SaveError> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager.bulk_set_disease(&ids, &disease)
+}
+
+/// 批量设置模板类型
+#[tauri::command]
+pub async fn bulk_set_template_type(
+    ids: Vec<String>,
+    template_type: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<BulkOperationResult>, SaveError> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager.bulk_set_template_type(&ids, &template_type)
+}
+
+/// 批量添加模板标签
+#[tauri::command]
+pub async fn bulk_add_template_tags(
+    ids: Vec<String>,
+    tags: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<BulkOperationResult>, SaveError> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager.bulk_add_tags(&ids, &tags)
+}
+
+/// 批量移除模板标签
+#[tauri::command]
+pub async fn bulk_remove_template_tags(
+    ids: Vec<String>,
+    tags: Vec<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<BulkOperationResult>, String> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .bulk_remove_tags(&ids, &tags)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
 /// 搜索模板
 #[tauri::command]
 pub async fn search_templates(
@@ -143,6 +346,23 @@ pub async fn search_templates(
         .map_err(|e| format!("Database error: {}", e))
 }
 
+/// 列出直接或间接引用了指定章节的所有章节，用于修改前评估影响范围
+#[tauri::command]
+pub async fn get_section_dependents(
+    template_id: String,
+    section_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<SectionDependent>, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_section_dependents(&template_id, &section_id)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
 /// 获取所有疾病分类及其模板数量
 #[tauri::command]
 pub async fn get_all_diseases(state: State<'_, AppState>) -> Result<Vec<Disease>, String> {
@@ -198,6 +418,208 @@ pub async fn save_tag(tag: Tag, state: State<'_, AppState>) -> Result<String, St
     Ok("Tag saved successfully".to_string())
 }
 
+/// 获取模板校验规则
+#[tauri::command]
+pub async fn get_validation_rules(state: State<'_, AppState>) -> Result<ValidationRules, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_validation_rules()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 保存模板校验规则
+#[tauri::command]
+pub async fn save_validation_rules(
+    rules: ValidationRules,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .set_validation_rules(&rules)
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok("Validation rules saved successfully".to_string())
+}
+
+/// 获取默认的粘贴方式
+#[tauri::command]
+pub async fn get_paste_strategy(state: State<'_, AppState>) -> Result<PasteStrategy, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_paste_strategy()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 保存默认的粘贴方式
+#[tauri::command]
+pub async fn save_paste_strategy(
+    strategy: PasteStrategy,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .set_paste_strategy(&strategy)
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok("Paste strategy saved successfully".to_string())
+}
+
+/// 获取窗口排除规则
+#[tauri::command]
+pub async fn get_window_exclusion_rules(
+    state: State<'_, AppState>,
+) -> Result<WindowExclusionRules, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_window_exclusion_rules()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 保存窗口排除规则，立即生效
+#[tauri::command]
+pub async fn save_window_exclusion_rules(
+    rules: WindowExclusionRules,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .set_window_exclusion_rules(&rules)
+        .map_err(|e| format!("Database error: {}", e))?;
+    history::set_rules(rules);
+    Ok("Window exclusion rules saved successfully".to_string())
+}
+
+/// 获取所有格式方案
+#[tauri::command]
+pub async fn get_format_profiles(state: State<'_, AppState>) -> Result<Vec<FormatProfile>, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_format_profiles()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 保存格式方案，返回由后端分配ID后的方案
+#[tauri::command]
+pub async fn save_format_profile(
+    profile: FormatProfile,
+    state: State<'_, AppState>,
+) -> Result<FormatProfile, String> {
+    if profile.name.trim().is_empty() {
+        return Err("Format profile name is empty".to_string());
+    }
+
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .save_format_profile(profile)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 删除格式方案，默认方案不能删除
+#[tauri::command]
+pub async fn delete_format_profile(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    if id == DEFAULT_PROFILE_ID {
+        return Err("The default format profile cannot be deleted".to_string());
+    }
+
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .delete_format_profile(&id)
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok("Format profile deleted successfully".to_string())
+}
+
+/// 获取所有程序粘贴方案
+#[tauri::command]
+pub async fn get_app_paste_profiles(
+    state: State<'_, AppState>,
+) -> Result<Vec<AppPasteProfile>, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_app_paste_profiles()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 保存程序粘贴方案，返回由后端分配ID后的方案
+#[tauri::command]
+pub async fn save_app_paste_profile(
+    profile: AppPasteProfile,
+    state: State<'_, AppState>,
+) -> Result<AppPasteProfile, String> {
+    if profile.name.trim().is_empty() {
+        return Err("App paste profile name is empty".to_string());
+    }
+    if profile.process_pattern.trim().is_empty() && profile.title_pattern.trim().is_empty() {
+        return Err("App paste profile needs a process or title pattern".to_string());
+    }
+
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .save_app_paste_profile(profile)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 删除程序粘贴方案
+#[tauri::command]
+pub async fn delete_app_paste_profile(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .delete_app_paste_profile(&id)
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok("App paste profile deleted successfully".to_string())
+}
+
 /// 重置标签
 #[tauri::command]
 pub async fn reset_tags(state: State<'_, AppState>) -> Result<String, String> {
@@ -226,6 +648,126 @@ pub async fn clear_templates(state: State<'_, AppState>) -> Result<String, Strin
     Ok("Templates cleared successfully".to_string())
 }
 
+/// 获取所有常用短语
+#[tauri::command]
+pub async fn get_all_snippets(state: State<'_, AppState>) -> Result<Vec<Snippet>, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_all_snippets()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 根据ID获取常用短语
+#[tauri::command]
+pub async fn get_snippet_by_id(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Option<Snippet>, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_snippet_by_id(&id)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 保存常用短语，返回由后端分配ID和时间戳后的短语
+#[tauri::command]
+pub async fn save_snippet(
+    snippet: Snippet,
+    state: State<'_, AppState>,
+) -> Result<Snippet, SaveError> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager.save_snippet(snippet)
+}
+
+/// 删除常用短语，仍被模板引用时拒绝删除
+#[tauri::command]
+pub async fn delete_snippet(id: String, state: State<'_, AppState>) -> Result<String, DeleteError> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager.delete_snippet(&id)?;
+    Ok("Snippet deleted successfully".to_string())
+}
+
+/// 记录一次常用短语的使用
+#[tauri::command]
+pub async fn record_snippet_usage(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .record_snippet_usage(&id)
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok("Snippet usage recorded successfully".to_string())
+}
+
+/// 搜索常用短语，可按分类筛选
+#[tauri::command]
+pub async fn search_snippets(
+    keyword: String,
+    category: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<Vec<Snippet>, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .search_snippets(&keyword, category.as_deref())
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 获取所有常用短语分类及其短语数量
+#[tauri::command]
+pub async fn get_all_snippet_categories(
+    state: State<'_, AppState>,
+) -> Result<Vec<SnippetCategory>, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_all_snippet_categories()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 列出直接或间接引用了指定常用短语的所有章节
+#[tauri::command]
+pub async fn get_snippet_dependents(
+    snippet_id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<SectionDependent>, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_snippet_dependents(&snippet_id)
+        .map_err(|e| format!("Database error: {}", e))
+}
+
 // 初始化示例数据
 // #[tauri::command]
 // pub async fn init_sample_data(state: State<'_, AppState>) -> Result<String, String> {
@@ -262,49 +804,3 @@ pub async fn clear_templates(state: State<'_, AppState>) -> Result<String, Strin
 
 //     Ok("Sample data initialized successfully".to_string())
 // }
-
-/// 创建悬浮搜索窗口
-#[tauri::command]
-pub async fn create_float_window(app_handle: AppHandle) -> Result<(), String> {
-    WebviewWindowBuilder::new(
-        &app_handle,
-        "floating",
-        WebviewUrl::App("float.html".into()),
-    )
-    .title("悬浮搜索")
-    .inner_size(300.0, 400.0)
-    .min_inner_size(250.0, 300.0)
-    .resizable(true)
-    .decorations(false)
-    .always_on_top(true)
-    .skip_taskbar(true)
-    .transparent(true)
-    .build()
-    .map_err(|e| format!("Failed to create float window: {}", e))?;
-
-    Ok(())
-}
-
-use mouse_position::mouse_position::Mouse;
-use serde_json::json;
-
-#[tauri::command]
-pub fn get_mouse_position() -> serde_json::Value {
-    /*
-     * because we set the window to ignore cursor events, we cannot use
-     * javascript to get the mouse position, so we use get mouse position manually
-     */
-    let position = Mouse::get_mouse_position();
-    match position {
-        Mouse::Position { x, y } => {
-            json!({
-                "clientX": x,
-                "clientY": y
-            })
-        }
-        Mouse::Error => {
-            println!("Error getting mouse position");
-            json!(null)
-        }
-    }
-}
@@ -0,0 +1,510 @@
+use crate::float::{
+    self, FloatGeometry, FloatWindowState, HitRect, DEFAULT_FLOAT_HEIGHT, DEFAULT_FLOAT_WIDTH,
+    FLOAT_WINDOW_LABEL,
+};
+use crate::platform;
+use crate::platform::history::{self, WindowInfo};
+use crate::{AppState, MAIN_WINDOW_TITLE};
+use tauri::webview::WebviewWindowBuilder;
+use tauri::{
+    AppHandle, Emitter, Manager, PhysicalPosition, PhysicalSize, State, WebviewUrl, WebviewWindow,
+    WindowEvent,
+};
+use tauri_plugin_global_shortcut::{GlobalShortcutExt, Shortcut};
+
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// 快捷键呼出悬浮窗口后发送给悬浮窗口的事件，载荷为捕获到的粘贴目标
+pub const FLOAT_SUMMONED_EVENT: &str = "float-summoned";
+
+/// 悬浮窗口的置顶、不透明度改变后发送给悬浮窗口的事件，载荷为 [`FloatWindowState`]
+pub const FLOAT_STATE_EVENT: &str = "float-state-changed";
+
+/// 鼠标移入、移出悬浮窗口时发送给悬浮窗口的事件
+pub const FLOAT_MOUSE_ENTER_EVENT: &str = "float-mouse-enter";
+pub const FLOAT_MOUSE_LEAVE_EVENT: &str = "float-mouse-leave";
+
+/// 检测鼠标位置的间隔
+const HOVER_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// 拖动、缩放悬浮窗口停止多久后保存位置和大小
+const GEOMETRY_SAVE_DELAY: Duration = Duration::from_millis(500);
+
+/// 待保存位置和大小的时间，没有待保存的改动时为空
+static GEOMETRY_SAVE_AT: Mutex<Option<Instant>> = Mutex::new(None);
+
+/// 页面报告的内容区域，页面报告前按整个窗口检测鼠标
+static HIT_RECT: Mutex<Option<HitRect>> = Mutex::new(None);
+
+// 读取当前显示器布局下保存的悬浮窗口状态，数据库未初始化时使用默认状态
+fn load_state(app: &AppHandle) -> Result<FloatWindowState, String> {
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("Failed to read monitors: {}", e))?;
+    let state = app.state::<AppState>();
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let Some(db_manager) = db.as_ref() else {
+        return Ok(FloatWindowState::default());
+    };
+    Ok(db_manager
+        .get_float_window_state(&float::layout_key(&monitors))
+        .map_err(|e| format!("Database error: {}", e))?
+        .unwrap_or_default())
+}
+
+// 修改并保存当前显示器布局下的悬浮窗口状态，返回修改后的状态
+fn update_state(
+    app: &AppHandle,
+    update: impl FnOnce(&mut FloatWindowState),
+) -> Result<FloatWindowState, String> {
+    let monitors = app
+        .available_monitors()
+        .map_err(|e| format!("Failed to read monitors: {}", e))?;
+    let layout = float::layout_key(&monitors);
+
+    let state = app.state::<AppState>();
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    let mut float_state = db_manager
+        .get_float_window_state(&layout)
+        .map_err(|e| format!("Database error: {}", e))?
+        .unwrap_or_default();
+    update(&mut float_state);
+    db_manager
+        .set_float_window_state(&layout, &float_state)
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(float_state)
+}
+
+// 保存悬浮窗口当前的位置和大小，窗口隐藏或最小化时不保存
+fn save_geometry(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    let read_failed = |e: tauri::Error| format!("Failed to read float window geometry: {}", e);
+    if !window.is_visible().map_err(read_failed)? || window.is_minimized().map_err(read_failed)? {
+        return Ok(());
+    }
+    let position = window.outer_position().map_err(read_failed)?;
+    let size = window.inner_size().map_err(read_failed)?;
+
+    update_state(app, |state| {
+        state.geometry = Some(FloatGeometry {
+            x: position.x,
+            y: position.y,
+            width: size.width,
+            height: size.height,
+        });
+    })?;
+    Ok(())
+}
+
+// 保存悬浮窗口的位置和大小，失败时只记录日志
+fn save_float_geometry(app: &AppHandle) {
+    if let Some(window) = app.get_webview_window(FLOAT_WINDOW_LABEL) {
+        if let Err(e) = save_geometry(app, &window) {
+            log::warn!("保存悬浮窗口位置失败: {}", e);
+        }
+    }
+}
+
+// 延迟保存位置和大小：拖动、缩放期间不断推迟，停止 GEOMETRY_SAVE_DELAY 后只保存一次
+fn schedule_geometry_save(app: &AppHandle) {
+    let Ok(mut save_at) = GEOMETRY_SAVE_AT.lock() else {
+        return;
+    };
+    let pending = save_at.is_some();
+    *save_at = Some(Instant::now() + GEOMETRY_SAVE_DELAY);
+    if pending {
+        return;
+    }
+    drop(save_at);
+
+    let app = app.clone();
+    thread::spawn(move || {
+        loop {
+            let remaining = {
+                let Ok(mut save_at) = GEOMETRY_SAVE_AT.lock() else {
+                    return;
+                };
+                let remaining = save_at
+                    .map(|at| at.saturating_duration_since(Instant::now()))
+                    .unwrap_or_default();
+                if remaining.is_zero() {
+                    *save_at = None;
+                    break;
+                }
+                remaining
+            };
+            thread::sleep(remaining);
+        }
+        save_float_geometry(&app);
+    });
+}
+
+// 记录悬浮窗口是否可见，下次启动时保持一致
+fn remember_visible(app: &AppHandle, visible: bool) {
+    if let Err(e) = update_state(app, |state| state.visible = visible) {
+        log::warn!("保存悬浮窗口显示状态失败: {}", e);
+    }
+}
+
+// 把保存的位置、大小和置顶状态应用到悬浮窗口
+fn apply_state(window: &WebviewWindow, state: &FloatWindowState) -> tauri::Result<()> {
+    if let Some(geometry) = state.geometry {
+        window.set_size(PhysicalSize::new(geometry.width, geometry.height))?;
+        window.set_position(PhysicalPosition::new(geometry.x, geometry.y))?;
+    }
+    window.set_always_on_top(state.pinned)
+}
+
+/// 获取悬浮窗口，不存在时按保存的状态创建，新创建的窗口保持隐藏
+pub fn ensure_float_window(app: &AppHandle) -> Result<WebviewWindow, String> {
+    if let Some(window) = app.get_webview_window(FLOAT_WINDOW_LABEL) {
+        return Ok(window);
+    }
+
+    let window = WebviewWindowBuilder::new(
+        app,
+        FLOAT_WINDOW_LABEL,
+        WebviewUrl::App("float.html".into()),
+    )
+    .title(MAIN_WINDOW_TITLE)
+    .inner_size(DEFAULT_FLOAT_WIDTH, DEFAULT_FLOAT_HEIGHT)
+    .min_inner_size(250.0, 300.0)
+    .resizable(true)
+    .decorations(false)
+    .skip_taskbar(true)
+    .transparent(true)
+    .shadow(false)
+    .visible(false)
+    .build()
+    .map_err(|e| format!("Failed to create float window: {}", e))?;
+
+    let state = load_state(app)?;
+    apply_state(&window, &state).map_err(|e| format!("Failed to restore float window: {}", e))?;
+
+    // 拖动、缩放停止后保存位置和大小，失去焦点或关闭前立即保存；
+    // 用户关闭悬浮窗口时与隐藏一样，下次启动时不再显示
+    let app_handle = app.clone();
+    window.on_window_event(move |event| match event {
+        WindowEvent::Moved(_) | WindowEvent::Resized(_) => schedule_geometry_save(&app_handle),
+        WindowEvent::Focused(false) => save_float_geometry(&app_handle),
+        WindowEvent::CloseRequested { .. } => {
+            save_float_geometry(&app_handle);
+            remember_visible(&app_handle, false);
+        }
+        _ => {}
+    });
+
+    Ok(window)
+}
+
+// 显示窗口并使其获得焦点，记录为可见
+fn show_window(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    window
+        .show()
+        .and_then(|_| window.unminimize())
+        .and_then(|_| window.set_focus())
+        .map_err(|e| format!("Failed to show float window: {}", e))?;
+    remember_visible(app, true);
+    Ok(())
+}
+
+// 保存位置后隐藏窗口，记录为隐藏
+fn hide_window(app: &AppHandle, window: &WebviewWindow) -> Result<(), String> {
+    if let Err(e) = save_geometry(app, window) {
+        log::warn!("保存悬浮窗口位置失败: {}", e);
+    }
+    window
+        .hide()
+        .map_err(|e| format!("Failed to hide float window: {}", e))?;
+    remember_visible(app, false);
+    Ok(())
+}
+
+/// 显示悬浮窗口，不存在时先创建
+pub fn show_float(app: &AppHandle) -> Result<(), String> {
+    show_window(app, &ensure_float_window(app)?)
+}
+
+/// 启动时按保存的状态创建悬浮窗口，上次隐藏时保持隐藏
+pub fn restore_float(app: &AppHandle) -> Result<(), String> {
+    let window = ensure_float_window(app)?;
+    if !load_state(app)?.visible {
+        return Ok(());
+    }
+    window
+        .show()
+        .map_err(|e| format!("Failed to show float window: {}", e))
+}
+
+/// 创建悬浮窗口，已存在时直接显示
+#[tauri::command]
+pub async fn create_float_window(app_handle: AppHandle) -> Result<(), String> {
+    show_float(&app_handle)
+}
+
+/// 显示悬浮窗口
+#[tauri::command]
+pub async fn show_float_window(app_handle: AppHandle) -> Result<(), String> {
+    show_float(&app_handle)
+}
+
+/// 隐藏悬浮窗口
+#[tauri::command]
+pub async fn hide_float_window(app_handle: AppHandle) -> Result<(), String> {
+    match app_handle.get_webview_window(FLOAT_WINDOW_LABEL) {
+        Some(window) => hide_window(&app_handle, &window),
+        None => Ok(()),
+    }
+}
+
+/// 切换悬浮窗口的显示状态，返回切换后是否可见
+#[tauri::command]
+pub async fn toggle_float_window(app_handle: AppHandle) -> Result<bool, String> {
+    let window = ensure_float_window(&app_handle)?;
+    let visible = window
+        .is_visible()
+        .map_err(|e| format!("Failed to read float window state: {}", e))?;
+    if visible {
+        hide_window(&app_handle, &window)?;
+    } else {
+        show_window(&app_handle, &window)?;
+    }
+    Ok(!visible)
+}
+
+/// 获取当前显示器布局下悬浮窗口的状态
+#[tauri::command]
+pub async fn get_float_window_state(app_handle: AppHandle) -> Result<FloatWindowState, String> {
+    load_state(&app_handle)
+}
+
+// 通知悬浮窗口状态已改变
+fn notify_state(app: &AppHandle, state: &FloatWindowState) {
+    if let Err(e) = app.emit_to(FLOAT_WINDOW_LABEL, FLOAT_STATE_EVENT, state) {
+        log::warn!("通知悬浮窗口状态失败: {}", e);
+    }
+}
+
+/// 设置悬浮窗口是否置顶
+#[tauri::command]
+pub async fn set_float_window_pinned(
+    pinned: bool,
+    app_handle: AppHandle,
+) -> Result<FloatWindowState, String> {
+    let state = update_state(&app_handle, |state| state.pinned = pinned)?;
+    if let Some(window) = app_handle.get_webview_window(FLOAT_WINDOW_LABEL) {
+        window
+            .set_always_on_top(pinned)
+            .map_err(|e| format!("Failed to pin float window: {}", e))?;
+    }
+    notify_state(&app_handle, &state);
+    Ok(state)
+}
+
+/// 设置悬浮窗口的不透明度，由悬浮窗口的页面负责应用
+#[tauri::command]
+pub async fn set_float_window_opacity(
+    opacity: f64,
+    app_handle: AppHandle,
+) -> Result<FloatWindowState, String> {
+    let state = update_state(&app_handle, |state| {
+        state.opacity = float::clamp_opacity(opacity)
+    })?;
+    notify_state(&app_handle, &state);
+    Ok(state)
+}
+
+// 把当前的前台窗口记录为粘贴目标，前台是本程序或被排除的窗口时沿用上一个窗口
+fn capture_target_window() -> Option<WindowInfo> {
+    match platform::current().foreground_window() {
+        Ok(Some(window)) if !history::is_excluded(&window) => {
+            history::record(window.clone());
+            Some(window)
+        }
+        Ok(_) => history::latest(),
+        Err(e) => {
+            log::warn!("读取前台窗口失败: {}", e);
+            history::latest()
+        }
+    }
+}
+
+// 把悬浮窗口移动到鼠标旁边，限制在鼠标所在显示器的工作区内
+fn move_to_cursor(app: &AppHandle, window: &WebviewWindow) -> tauri::Result<()> {
+    let cursor = app.cursor_position()?;
+    let monitor = match app.monitor_from_point(cursor.x, cursor.y)? {
+        Some(monitor) => Some(monitor),
+        None => window.primary_monitor()?,
+    };
+    let Some(monitor) = monitor else {
+        return Ok(());
+    };
+
+    let area = monitor.work_area();
+    let position = float::place_near_cursor(
+        PhysicalPosition::new(cursor.x as i32, cursor.y as i32),
+        window.outer_size()?,
+        area.position,
+        area.size,
+    );
+    window.set_position(position)
+}
+
+/// 呼出悬浮窗口：捕获当前的前台窗口作为粘贴目标，在鼠标旁边显示悬浮窗口并使其获得焦点
+pub fn summon_float_window(app: &AppHandle) -> Result<(), String> {
+    let target = capture_target_window();
+
+    let window = ensure_float_window(app)?;
+    move_to_cursor(app, &window).map_err(|e| format!("Failed to move float window: {}", e))?;
+    show_window(app, &window)?;
+
+    window
+        .emit(FLOAT_SUMMONED_EVENT, &target)
+        .map_err(|e| format!("Failed to notify float window: {}", e))
+}
+
+/// 注册呼出悬浮窗口的快捷键，替换之前注册的快捷键，`hotkey` 为空时只取消注册
+pub fn register_float_hotkey(app: &AppHandle, hotkey: &str) -> Result<(), String> {
+    let shortcut = match hotkey.trim() {
+        "" => None,
+        hotkey => Some(
+            hotkey
+                .parse::<Shortcut>()
+                .map_err(|e| format!("Invalid hotkey {}: {}", hotkey, e))?,
+        ),
+    };
+
+    let global_shortcut = app.global_shortcut();
+    global_shortcut
+        .unregister_all()
+        .map_err(|e| format!("Failed to unregister hotkey: {}", e))?;
+    if let Some(shortcut) = shortcut {
+        global_shortcut
+            .register(shortcut)
+            .map_err(|e| format!("Failed to register hotkey {}: {}", hotkey, e))?;
+    }
+    Ok(())
+}
+
+/// 获取呼出悬浮窗口的快捷键
+#[tauri::command]
+pub async fn get_float_hotkey(state: State<'_, AppState>) -> Result<String, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_float_hotkey()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 保存并立即注册呼出悬浮窗口的快捷键，注册失败（如已被其他程序占用）时恢复原来的快捷键
+#[tauri::command]
+pub async fn save_float_hotkey(
+    hotkey: String,
+    app_handle: AppHandle,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    // 注册快捷键时不持有数据库锁，快捷键的回调也会访问数据库
+    let previous = {
+        let db = state
+            .db
+            .lock()
+            .map_err(|e| format!("Failed to lock database: {}", e))?;
+        let db_manager = db.as_ref().ok_or("Database not initialized")?;
+        db_manager
+            .get_float_hotkey()
+            .map_err(|e| format!("Database error: {}", e))?
+    };
+
+    if let Err(e) = register_float_hotkey(&app_handle, &hotkey) {
+        let _ = register_float_hotkey(&app_handle, &previous);
+        return Err(e);
+    }
+
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .set_float_hotkey(hotkey.trim())
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok("Float hotkey saved successfully".to_string())
+}
+
+/// 设置悬浮窗口中页面内容实际占据的区域，由页面在展开、收起或内容大小改变时报告
+#[tauri::command]
+pub async fn set_float_hit_rect(rect: HitRect) -> Result<(), String> {
+    *HIT_RECT
+        .lock()
+        .map_err(|e| format!("Failed to lock hit rect: {}", e))? = Some(rect);
+    Ok(())
+}
+
+// 鼠标是否在可见的悬浮窗口的内容区域内，窗口不存在或隐藏时返回 `None`
+fn float_hovered(app: &AppHandle) -> tauri::Result<Option<(WebviewWindow, bool)>> {
+    let Some(window) = app.get_webview_window(FLOAT_WINDOW_LABEL) else {
+        return Ok(None);
+    };
+    if !window.is_visible()? || window.is_minimized()? {
+        return Ok(None);
+    }
+    let hit_rect = HIT_RECT.lock().ok().and_then(|rect| *rect);
+    let (position, size) = match hit_rect {
+        Some(rect) => rect.on_screen(window.inner_position()?),
+        None => (window.outer_position()?, window.outer_size()?),
+    };
+    let inside = float::contains(position, size, app.cursor_position()?);
+    Ok(Some((window, inside)))
+}
+
+/// 在后台跟踪鼠标是否悬停在悬浮窗口上：鼠标移入时接收鼠标事件，移出后恢复点击穿透，
+/// 并向悬浮窗口发送移入、移出事件
+pub fn track_float_hover(app: AppHandle) {
+    thread::spawn(move || {
+        // 上一次检测的结果，窗口隐藏后重置，重新显示时总会同步一次
+        let mut hovered: Option<bool> = None;
+        loop {
+            thread::sleep(HOVER_POLL_INTERVAL);
+
+            let (window, inside) = match float_hovered(&app) {
+                Ok(Some(result)) => result,
+                Ok(None) => {
+                    hovered = None;
+                    continue;
+                }
+                Err(e) => {
+                    log::debug!("检测鼠标位置失败: {}", e);
+                    continue;
+                }
+            };
+            if hovered == Some(inside) {
+                continue;
+            }
+
+            if let Err(e) = window.set_ignore_cursor_events(!inside) {
+                log::warn!("设置悬浮窗口点击穿透失败: {}", e);
+                continue;
+            }
+            hovered = Some(inside);
+            let event = if inside {
+                FLOAT_MOUSE_ENTER_EVENT
+            } else {
+                FLOAT_MOUSE_LEAVE_EVENT
+            };
+            if let Err(e) = app.emit_to(FLOAT_WINDOW_LABEL, event, ()) {
+                log::warn!("通知悬浮窗口鼠标位置失败: {}", e);
+            }
+        }
+    });
+}
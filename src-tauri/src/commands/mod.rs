@@ -1,5 +1,11 @@
 mod database;
+mod float;
 mod paste;
+mod render;
+mod suggestion;
 
 pub use database::*;
+pub use float::*;
 pub use paste::*;
+pub use render::*;
+pub use suggestion::*;
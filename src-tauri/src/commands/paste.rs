@@ -1,23 +1,20 @@
-use crate::MAIN_WINDOW_TITLE;
+use super::render::{load_profile, load_template};
+use crate::database::Template;
+use crate::format::FormatProfile;
+use crate::input::{self, NewlineMode, PasteStrategy, DEFAULT_FOCUS_DELAY, DEFAULT_RESTORE_DELAY};
+use crate::platform::history::{self, WindowInfo};
+use crate::platform::{self, ClipboardContents, PasteError, PasteKeys, WindowId};
+use crate::render;
+use crate::AppState;
 
-use std::ffi::OsString;
-use std::os::windows::ffi::OsStringExt;
-use std::ptr;
-use std::sync::Mutex;
+use std::collections::HashMap;
 
 use enigo::{
     Direction::{Click, Press, Release},
-    Enigo, Key, Keyboard, Settings,
+    Enigo, InputError, InputResult, Key, Keyboard, Settings,
 };
-use tauri::command;
-use winapi::shared::minwindef::DWORD;
-use winapi::shared::windef::{HWINEVENTHOOK, HWND};
-use winapi::um::winuser::{
-    GetWindowTextLengthW, GetWindowTextW, SetForegroundWindow, SetWinEventHook,
-    EVENT_SYSTEM_FOREGROUND, WINEVENT_OUTOFCONTEXT,
-};
-
-static PREVIOUS_WINDOW: Mutex<Option<isize>> = Mutex::new(None);
+use serde::{Deserialize, Serialize};
+use tauri::{command, State};
 
 fn wait(millis: u64) {
     use std::{thread, time};
@@ -25,98 +22,437 @@ fn wait(millis: u64) {
     thread::sleep(time::Duration::from_millis(millis));
 }
 
-// 获取窗口标题
-unsafe fn get_window_title(hwnd: HWND) -> String {
-    let length = GetWindowTextLengthW(hwnd);
+// 监听窗口切换
+pub fn observe_app() {
+    if let Err(e) = platform::current().observe_foreground() {
+        log::error!("监听窗口切换失败: {}", e);
+    }
+}
+
+// 聚焦粘贴目标：指定了窗口时切换到该窗口并记住本次选择，否则切换到上一个窗口
+fn focus_target_window(target_window: Option<WindowId>) -> Result<(), PasteError> {
+    let Some(window) = target_window else {
+        let window = history::latest().ok_or(PasteError::NoPreviousWindow)?;
+        return platform::current().focus_window(window.id);
+    };
+    platform::current().focus_window(window)?;
+    history::set_last_target(window);
+    Ok(())
+}
+
+// 获取最近的前台窗口，第一个即为粘贴的目标
+#[command]
+pub fn get_window_history() -> Vec<WindowInfo> {
+    history::recent_windows()
+}
+
+// 可选的粘贴目标
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteTargets {
+    // 当前的顶层窗口，按前后顺序排列
+    pub windows: Vec<WindowInfo>,
+    // 本次运行中最后一次选择的目标，窗口已关闭时为空
+    pub last_target: Option<WindowId>,
+}
+
+// 列出可以作为粘贴目标的窗口
+#[command]
+pub fn get_paste_targets() -> Result<PasteTargets, PasteError> {
+    let windows = platform::current().list_windows()?;
+    let last_target =
+        history::last_target().filter(|&target| windows.iter().any(|window| window.id == target));
+    Ok(PasteTargets {
+        windows,
+        last_target,
+    })
+}
+
+// 创建输入模拟器
+fn new_enigo() -> Result<Enigo, PasteError> {
+    Enigo::new(&Settings::default()).map_err(|e| PasteError::BackendUnavailable {
+        message: e.to_string(),
+    })
+}
 
-    if length == 0 {
-        return String::new();
+// 发送按键失败
+fn key_send_failed(e: InputError) -> PasteError {
+    PasteError::KeySendFailed {
+        message: e.to_string(),
     }
+}
 
-    let mut buffer: Vec<u16> = vec![0; (length + 1) as usize];
+// 写入剪贴板文本
+fn set_clipboard_text(text: &str) -> Result<(), PasteError> {
+    platform::current().write_clipboard(&ClipboardContents::text(text))
+}
 
-    GetWindowTextW(hwnd, buffer.as_mut_ptr(), length + 1);
+// 执行会占用剪贴板的操作，完成后等待目标窗口读取剪贴板，再恢复用户原来的文本和 HTML 内容
+//
+// 图片、文件等其他格式无法保存，剪贴板中只有这些格式时粘贴后保留模板文本。
+fn preserving_clipboard<T>(
+    settings: &PasteSettings,
+    action: impl FnOnce() -> Result<T, PasteError>,
+) -> Result<T, PasteError> {
+    if matches!(settings.strategy, PasteStrategy::Type { .. }) {
+        return action();
+    }
 
-    OsString::from_wide(&buffer[..length as usize])
-        .to_string_lossy()
-        .into_owned()
+    let platform = platform::current();
+    let snapshot = platform.read_clipboard().unwrap_or_else(|e| {
+        log::warn!("保存剪贴板内容失败: {}", e);
+        ClipboardContents::default()
+    });
+    let result = action();
+    wait(settings.restore_delay);
+    if !snapshot.is_empty() {
+        if let Err(e) = platform.write_clipboard(&snapshot) {
+            log::warn!("恢复剪贴板内容失败: {}", e);
+        }
+    }
+    result
 }
 
-// 定义事件钩子回调函数
-unsafe extern "system" fn event_hook_callback(
-    _h_win_event_hook: HWINEVENTHOOK,
-    event: DWORD,
-    hwnd: HWND,
-    _id_object: i32,
-    _id_child: i32,
-    _dw_event_thread: DWORD,
-    _dwms_event_time: DWORD,
-) {
-    if event == EVENT_SYSTEM_FOREGROUND {
-        let window_title = get_window_title(hwnd);
+// 逐字模拟键盘输入，换行使用回车键，`delay` 为每个字符之间的间隔
+fn type_text(enigo: &mut Enigo, text: &str, delay: u64) -> InputResult<()> {
+    for c in text.chars().filter(|&c| c != '\r') {
+        match c {
+            '\n' => enigo.key(Key::Return, Click)?,
+            _ => enigo.text(c.encode_utf8(&mut [0; 4]))?,
+        }
+        if delay > 0 {
+            wait(delay);
+        }
+    }
+    Ok(())
+}
 
-        if window_title == MAIN_WINDOW_TITLE {
-            return;
+// 按指定方式把文本送入当前焦点窗口
+fn insert_text(enigo: &mut Enigo, text: &str, strategy: PasteStrategy) -> Result<(), PasteError> {
+    let keys = match strategy {
+        PasteStrategy::ShiftInsert => PasteKeys::ShiftInsert,
+        PasteStrategy::CtrlV => PasteKeys::CtrlV,
+        PasteStrategy::Type { delay } => {
+            return type_text(enigo, text, delay).map_err(key_send_failed)
         }
+    };
+    set_clipboard_text(text)?;
+    platform::current().send_paste_keys(keys)
+}
+
+// 本次粘贴使用的设置
+struct PasteSettings {
+    strategy: PasteStrategy,
+    focus_delay: u64,
+    restore_delay: u64,
+    newline: NewlineMode,
+    format_profile_id: Option<String>,
+}
 
-        let mut previous_window = PREVIOUS_WINDOW.lock().unwrap();
-        let _ = previous_window.insert(hwnd as isize);
+// 读取粘贴目标的窗口信息，用于匹配程序粘贴方案
+pub(super) fn target_window_info(target_window: Option<WindowId>) -> Option<WindowInfo> {
+    match target_window {
+        Some(window) => platform::current().window_info(window).ok(),
+        None => history::latest(),
     }
 }
 
-// 监听窗口切换
-pub fn observe_app() {
-    unsafe {
-        // 设置事件钩子
-        let hook = SetWinEventHook(
-            EVENT_SYSTEM_FOREGROUND,
-            EVENT_SYSTEM_FOREGROUND,
-            ptr::null_mut(),
-            Some(event_hook_callback),
-            0,
-            0,
-            WINEVENT_OUTOFCONTEXT,
-        );
-
-        if hook.is_null() {
-            log::error!("设置事件钩子失败");
-            return;
+// 读取粘贴设置：优先使用请求中指定的粘贴方式，其次使用匹配目标窗口的程序粘贴方案，
+// 都没有时使用设置中的默认方式
+fn load_settings(
+    strategy: Option<PasteStrategy>,
+    target_window: Option<WindowId>,
+    state: &State<'_, AppState>,
+) -> Result<PasteSettings, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+
+    let profiles = db_manager
+        .get_app_paste_profiles()
+        .map_err(|e| format!("Database error: {}", e))?;
+    let app_profile = target_window_info(target_window)
+        .and_then(|window| input::find_app_profile(&profiles, &window).cloned());
+
+    let settings = match app_profile {
+        Some(profile) => PasteSettings {
+            strategy: strategy.unwrap_or(profile.strategy),
+            focus_delay: profile.focus_delay,
+            restore_delay: profile.restore_delay,
+            newline: profile.newline,
+            format_profile_id: profile.format_profile_id,
+        },
+        None => PasteSettings {
+            strategy: match strategy {
+                Some(strategy) => strategy,
+                None => db_manager
+                    .get_paste_strategy()
+                    .map_err(|e| format!("Database error: {}", e))?,
+            },
+            focus_delay: DEFAULT_FOCUS_DELAY,
+            restore_delay: DEFAULT_RESTORE_DELAY,
+            newline: NewlineMode::default(),
+            format_profile_id: None,
+        },
+    };
+    Ok(settings)
+}
+
+// 按左方向键将光标向前移动指定的字符数
+fn move_caret_left(enigo: &mut Enigo, count: usize) -> InputResult<()> {
+    for _ in 0..count {
+        enigo.key(Key::LeftArrow, Click)?;
+    }
+    Ok(())
+}
+
+// 读取模板并按给定顺序保留选中的章节，`section_indexes` 为空时保留所有章节
+fn load_selected_sections(
+    id: &str,
+    section_indexes: &[usize],
+    state: &State<'_, AppState>,
+) -> Result<Template, String> {
+    let mut template = load_template(id, state)?;
+
+    if !section_indexes.is_empty() {
+        template.sections = section_indexes
+            .iter()
+            .map(|&index| {
+                template
+                    .sections
+                    .get(index)
+                    .cloned()
+                    .ok_or_else(|| format!("Section index {} out of range", index))
+            })
+            .collect::<Result<_, _>>()?;
+    }
+
+    Ok(template)
+}
+
+// 把文本粘贴到目标窗口，并将光标移动到光标标记处
+fn paste_to_window(
+    text: &str,
+    settings: &PasteSettings,
+    target_window: Option<WindowId>,
+) -> Result<(), PasteError> {
+    let (text, cursor) = render::take_cursor(text);
+    let text = settings.newline.apply(&text);
+
+    let mut enigo = new_enigo()?;
+
+    focus_target_window(target_window)?;
+
+    wait(settings.focus_delay);
+
+    preserving_clipboard(settings, || {
+        insert_text(&mut enigo, &text, settings.strategy)?;
+
+        if let Some(count) = cursor.filter(|&count| count > 0) {
+            // 等待目标窗口处理完粘贴，否则方向键可能先于文本到达
+            wait(100);
+            move_caret_left(&mut enigo, count).map_err(key_send_failed)?;
+        }
+        Ok(())
+    })
+}
+
+// 按给定顺序渲染模板中选中的章节，按指定方式粘贴到目标窗口，未指定目标时粘贴到上一个窗口
+//
+// 未指定粘贴方式和格式方案时，使用匹配目标窗口的程序粘贴方案中的设置
+#[command]
+pub async fn paste(
+    id: String,
+    section_indexes: Vec<usize>,
+    profile_id: Option<String>,
+    values: Option<HashMap<String, String>>,
+    strategy: Option<PasteStrategy>,
+    target_window: Option<WindowId>,
+    state: State<'_, AppState>,
+) -> Result<(), PasteError> {
+    let settings = load_settings(strategy, target_window, &state)?;
+    let template = load_selected_sections(&id, &section_indexes, &state)?;
+    let profile_id = profile_id.or_else(|| settings.format_profile_id.clone());
+    let profile = load_profile(profile_id.as_deref(), &state)?;
+    let text = render::render_template(&template, &values.unwrap_or_default(), &profile).text;
+    paste_to_window(&text, &settings, target_window)
+}
+
+// 将给定文本粘贴到目标窗口，例如常用短语，未指定目标时粘贴到上一个窗口
+#[command]
+pub async fn paste_text(
+    text: String,
+    strategy: Option<PasteStrategy>,
+    target_window: Option<WindowId>,
+    state: State<'_, AppState>,
+) -> Result<(), PasteError> {
+    let settings = load_settings(strategy, target_window, &state)?;
+    paste_to_window(&text, &settings, target_window)
+}
+
+// 逐项粘贴的设置
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FieldPasteOptions {
+    // 每个章节粘贴后发送的按键序列，例如 ["Tab"]、["Tab", "Tab"]、["Shift+Tab"]
+    pub navigation_keys: Vec<String>,
+    // 序列中相邻按键之间的间隔（毫秒）
+    pub key_delay: u64,
+    // 粘贴章节前后等待目标输入框响应的时间（毫秒）
+    pub section_delay: u64,
+    // 跳过内容为空的章节，不粘贴也不发送按键
+    pub skip_empty_sections: bool,
+}
+
+impl Default for FieldPasteOptions {
+    fn default() -> Self {
+        FieldPasteOptions {
+            navigation_keys: vec!["Tab".to_string()],
+            key_delay: 50,
+            section_delay: 100,
+            skip_empty_sections: false,
         }
     }
 }
 
-// 获取上一个窗口
-pub fn get_previous_window() -> Option<isize> {
-    return PREVIOUS_WINDOW.lock().unwrap().clone();
+// 组合键，例如 Ctrl+Shift+Tab
+struct KeyChord {
+    modifiers: Vec<Key>,
+    key: Key,
 }
 
-// 聚焦上一个窗口
-fn focus_previous_window() {
-    unsafe {
-        let hwnd = match get_previous_window() {
-            Some(hwnd) => hwnd as HWND,
-            None => return,
-        };
+// 解析按键名称，大小写不敏感，组合键以 + 连接
+fn parse_key_chord(name: &str) -> Result<KeyChord, String> {
+    let parts: Vec<&str> = name.split('+').map(|part| part.trim()).collect();
+    let (key_name, modifier_names) = parts
+        .split_last()
+        .filter(|(key_name, _)| !key_name.is_empty())
+        .ok_or_else(|| format!("Invalid key: {}", name))?;
 
-        if hwnd.is_null() {
-            return;
+    let modifiers = modifier_names
+        .iter()
+        .map(|modifier| match modifier.to_lowercase().as_str() {
+            "ctrl" | "control" => Ok(Key::Control),
+            "shift" => Ok(Key::Shift),
+            "alt" => Ok(Key::Alt),
+            _ => Err(format!("Invalid modifier key: {}", modifier)),
+        })
+        .collect::<Result<_, _>>()?;
+
+    let lower = key_name.to_lowercase();
+    let key = match lower.as_str() {
+        "tab" => Key::Tab,
+        "enter" | "return" => Key::Return,
+        "space" => Key::Space,
+        "escape" | "esc" => Key::Escape,
+        "backspace" => Key::Backspace,
+        "delete" => Key::Delete,
+        "up" => Key::UpArrow,
+        "down" => Key::DownArrow,
+        "left" => Key::LeftArrow,
+        "right" => Key::RightArrow,
+        "home" => Key::Home,
+        "end" => Key::End,
+        "pageup" => Key::PageUp,
+        "pagedown" => Key::PageDown,
+        "f1" => Key::F1,
+        "f2" => Key::F2,
+        "f3" => Key::F3,
+        "f4" => Key::F4,
+        "f5" => Key::F5,
+        "f6" => Key::F6,
+        "f7" => Key::F7,
+        "f8" => Key::F8,
+        "f9" => Key::F9,
+        "f10" => Key::F10,
+        "f11" => Key::F11,
+        "f12" => Key::F12,
+        _ => {
+            let mut chars = lower.chars();
+            match (chars.next(), chars.next()) {
+                (Some(c), None) => Key::Unicode(c),
+                _ => return Err(format!("Invalid key: {}", key_name)),
+            }
         }
+    };
+
+    Ok(KeyChord { modifiers, key })
+}
 
-        SetForegroundWindow(hwnd);
+// 发送组合键：依次按下修饰键，点击主键，再逆序释放修饰键
+fn send_key_chord(enigo: &mut Enigo, chord: &KeyChord) -> InputResult<()> {
+    for modifier in &chord.modifiers {
+        enigo.key(*modifier, Press)?;
+    }
+    enigo.key(chord.key, Click)?;
+    for modifier in chord.modifiers.iter().rev() {
+        enigo.key(*modifier, Release)?;
     }
+    Ok(())
 }
 
-// 粘贴
+// 将模板中选中的章节逐个粘贴到目标窗口的各个输入框，章节之间发送导航按键
 #[command]
-pub async fn paste() {
-    let mut enigo = Enigo::new(&Settings::default()).unwrap();
+pub async fn paste_fields(
+    id: String,
+    section_indexes: Vec<usize>,
+    values: Option<HashMap<String, String>>,
+    options: Option<FieldPasteOptions>,
+    strategy: Option<PasteStrategy>,
+    target_window: Option<WindowId>,
+    state: State<'_, AppState>,
+) -> Result<(), PasteError> {
+    let options = options.unwrap_or_default();
+    let settings = load_settings(strategy, target_window, &state)?;
+    let navigation = options
+        .navigation_keys
+        .iter()
+        .map(|name| parse_key_chord(name))
+        .collect::<Result<Vec<_>, _>>()?;
 
-    focus_previous_window();
+    let template = load_selected_sections(&id, &section_indexes, &state)?;
+    // 每个输入框只粘贴章节内容，不含标题
+    let rendered = render::render_template(
+        &template,
+        &values.unwrap_or_default(),
+        &FormatProfile::default(),
+    );
+    let sections: Vec<_> = rendered
+        .sections
+        .into_iter()
+        .filter(|section| !options.skip_empty_sections || !section.content.trim().is_empty())
+        .collect();
 
-    wait(100);
+    let mut enigo = new_enigo()?;
 
-    enigo.key(Key::Shift, Press).unwrap();
-    // insert 的微软虚拟键码：https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
-    enigo.key(Key::Other(0x2D), Click).unwrap();
-    enigo.key(Key::Shift, Release).unwrap();
+    focus_target_window(target_window)?;
+
+    wait(settings.focus_delay);
+
+    // 所有章节粘贴完成后才恢复剪贴板，避免与下一个章节的写入冲突
+    preserving_clipboard(&settings, || {
+        for (index, section) in sections.iter().enumerate() {
+            // 逐项粘贴后会跳转到下一个输入框，光标标记只去除、不移动光标
+            let (content, _) = render::take_cursor(&section.content);
+            let content = settings.newline.apply(&content);
+            if !content.is_empty() {
+                insert_text(&mut enigo, &content, settings.strategy)?;
+                wait(options.section_delay);
+            }
+
+            if index + 1 == sections.len() {
+                break;
+            }
+            for (step, chord) in navigation.iter().enumerate() {
+                if step > 0 {
+                    wait(options.key_delay);
+                }
+                send_key_chord(&mut enigo, chord).map_err(key_send_failed)?;
+            }
+            wait(options.section_delay);
+        }
+        Ok(())
+    })
 }
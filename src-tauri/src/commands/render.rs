@@ -0,0 +1,79 @@
+use crate::database::Template;
+use crate::format::{FormatProfile, DEFAULT_PROFILE_ID};
+use crate::render::{self, FormField, RenderError, RenderedTemplate, TemplateVariable};
+use crate::AppState;
+use std::collections::HashMap;
+use tauri::State;
+
+/// 从数据库中读取模板，并解析其中的章节引用
+pub(super) fn load_template(id: &str, state: &State<'_, AppState>) -> Result<Template, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    let template = db_manager
+        .get_template_by_id(id)
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or("Template not found")?;
+    db_manager.resolve_template(&template)
+}
+
+/// 读取格式方案，未指定时使用默认方案
+pub(super) fn load_profile(
+    id: Option<&str>,
+    state: &State<'_, AppState>,
+) -> Result<FormatProfile, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_format_profile(id.unwrap_or(DEFAULT_PROFILE_ID))
+        .map_err(|e| format!("Database error: {}", e))?
+        .ok_or_else(|| "Format profile not found".to_string())
+}
+
+/// 获取模板中的变量
+#[tauri::command]
+pub async fn get_template_variables(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<TemplateVariable>, String> {
+    let template = load_template(&id, &state)?;
+    Ok(render::list_variables(&template))
+}
+
+/// 获取模板的填写表单
+#[tauri::command]
+pub async fn get_template_form(
+    id: String,
+    state: State<'_, AppState>,
+) -> Result<Vec<FormField>, String> {
+    let template = load_template(&id, &state)?;
+    Ok(render::form_schema(&template))
+}
+
+/// 校验填写的值并按格式方案渲染模板，返回可直接写入剪贴板粘贴的文本
+#[tauri::command]
+pub async fn render_template(
+    id: String,
+    values: HashMap<String, String>,
+    profile_id: Option<String>,
+    state: State<'_, AppState>,
+) -> Result<RenderedTemplate, RenderError> {
+    let template = load_template(&id, &state)?;
+    let profile = load_profile(profile_id.as_deref(), &state)?;
+    let errors = render::validate_values(&render::form_schema(&template), &values);
+    if !errors.is_empty() {
+        return Err(RenderError::Validation { errors });
+    }
+    let mut rendered = render::render_template(&template, &values, &profile);
+    // 光标标记只在粘贴时使用，复制的文本中不保留
+    rendered.text = render::take_cursor(&rendered.text).0;
+    for section in &mut rendered.sections {
+        section.content = render::take_cursor(&section.content).0;
+    }
+    Ok(rendered)
+}
@@ -0,0 +1,61 @@
+use super::paste::target_window_info;
+use crate::platform::WindowId;
+use crate::suggestion::{self, CompiledRules, SuggestionRule, TemplateSuggestions};
+use crate::AppState;
+use tauri::State;
+
+/// 获取模板推荐规则
+#[tauri::command]
+pub async fn get_suggestion_rules(
+    state: State<'_, AppState>,
+) -> Result<Vec<SuggestionRule>, String> {
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    db_manager
+        .get_suggestion_rules()
+        .map_err(|e| format!("Database error: {}", e))
+}
+
+/// 保存模板推荐规则，正则表达式无效时不保存
+#[tauri::command]
+pub async fn save_suggestion_rules(
+    rules: Vec<SuggestionRule>,
+    state: State<'_, AppState>,
+) -> Result<String, String> {
+    let compiled = CompiledRules::compile(&rules)?;
+
+    let mut db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_mut().ok_or("Database not initialized")?;
+    db_manager
+        .set_suggestion_rules(&rules)
+        .map_err(|e| format!("Database error: {}", e))?;
+    suggestion::set_rules(compiled);
+    Ok("Suggestion rules saved successfully".to_string())
+}
+
+/// 根据粘贴目标的窗口标题推荐模板，未指定目标时使用上一个窗口
+#[tauri::command]
+pub async fn suggest_templates(
+    target_window: Option<WindowId>,
+    state: State<'_, AppState>,
+) -> Result<TemplateSuggestions, String> {
+    let Some(window) = target_window_info(target_window) else {
+        return Ok(TemplateSuggestions::default());
+    };
+
+    let db = state
+        .db
+        .lock()
+        .map_err(|e| format!("Failed to lock database: {}", e))?;
+    let db_manager = db.as_ref().ok_or("Database not initialized")?;
+    let templates = db_manager
+        .get_all_templates()
+        .map_err(|e| format!("Database error: {}", e))?;
+    Ok(suggestion::suggest(&window.title, templates))
+}
@@ -0,0 +1,322 @@
+use crate::database::{SectionReference, Snippet, Template, TemplateSection};
+use crate::validation::FieldError;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashSet, VecDeque};
+
+/// 依赖某个章节的章节
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionDependent {
+    pub template_id: String,
+    pub template_title: String,
+    pub section_id: String,
+    pub section_title: String,
+    /// 引用层级，1 表示直接引用
+    pub depth: usize,
+}
+
+/// 章节引用的数据来源
+pub trait ReferenceSource {
+    fn template(&self, id: &str) -> Option<Template>;
+    fn snippet(&self, id: &str) -> Option<Snippet>;
+}
+
+/// 用待保存的模板覆盖数据来源中的同ID模板，并隐藏将被删除的模板，用于保存前检查引用
+struct PendingTemplates<'a> {
+    source: &'a dyn ReferenceSource,
+    templates: &'a [Template],
+    removed_ids: &'a [String],
+}
+
+impl ReferenceSource for PendingTemplates<'_> {
+    fn template(&self, id: &str) -> Option<Template> {
+        if let Some(template) = self.templates.iter().find(|template| template.id == id) {
+            Some(template.clone())
+        } else if self.removed_ids.iter().any(|removed| removed == id) {
+            None
+        } else {
+            self.source.template(id)
+        }
+    }
+
+    fn snippet(&self, id: &str) -> Option<Snippet> {
+        self.source.snippet(id)
+    }
+}
+
+/// 解析过程中经过的章节，用于检测循环引用
+struct Visit {
+    template_id: String,
+    section_id: String,
+    label: String,
+}
+
+/// 递归解析章节内容，`path` 为当前的引用链
+fn resolve_content(
+    section: &TemplateSection,
+    source: &dyn ReferenceSource,
+    path: &mut Vec<Visit>,
+) -> Result<String, String> {
+    let Some(reference) = &section.reference else {
+        return Ok(section.content.clone());
+    };
+
+    match reference {
+        SectionReference::Section {
+            template_id,
+            section_id,
+        } => {
+            let template = source
+                .template(template_id)
+                .ok_or_else(|| format!("章节「{}」引用的模板不存在", section.title))?;
+            let target = template
+                .sections
+                .iter()
+                .find(|target| target.id.as_deref() == Some(section_id.as_str()))
+                .ok_or_else(|| {
+                    format!(
+                        "章节「{}」引用的章节在模板「{}」中不存在",
+                        section.title, template.title
+                    )
+                })?;
+
+            let label = format!("{}/{}", template.title, target.title);
+            if path
+                .iter()
+                .any(|visit| visit.template_id == *template_id && visit.section_id == *section_id)
+            {
+                let chain: Vec<&str> = path.iter().map(|visit| visit.label.as_str()).collect();
+                return Err(format!(
+                    "章节引用存在循环：{} → {}",
+                    chain.join(" → "),
+                    label
+                ));
+            }
+
+            path.push(Visit {
+                template_id: template_id.clone(),
+                section_id: section_id.clone(),
+                label,
+            });
+            let content = resolve_content(target, source, path)?;
+            path.pop();
+            Ok(content)
+        }
+        SectionReference::Snippet { snippet_id } => source
+            .snippet(snippet_id)
+            .map(|snippet| snippet.content)
+            .ok_or_else(|| format!("章节「{}」引用的常用短语不存在", section.title)),
+    }
+}
+
+/// 从模板的某个章节开始解析引用
+fn resolve_section(
+    template: &Template,
+    section: &TemplateSection,
+    source: &dyn ReferenceSource,
+) -> Result<String, String> {
+    let mut path = vec![Visit {
+        template_id: template.id.clone(),
+        section_id: section.id.clone().unwrap_or_default(),
+        label: format!("{}/{}", template.title, section.title),
+    }];
+    resolve_content(section, source, &mut path)
+}
+
+/// 解析模板中所有章节的引用，返回用被引用内容替换后的模板
+///
+/// 引用的目标不存在或存在循环引用时返回错误。
+pub fn resolve_sections(
+    template: &Template,
+    source: &dyn ReferenceSource,
+) -> Result<Template, String> {
+    let mut resolved = template.clone();
+    for section in &mut resolved.sections {
+        if section.reference.is_some() {
+            section.content = resolve_section(template, section, source)?;
+            section.reference = None;
+        }
+    }
+    Ok(resolved)
+}
+
+/// 检查待保存模板中的章节引用，按字段路径返回错误
+pub fn check_references(template: &Template, source: &dyn ReferenceSource) -> Vec<FieldError> {
+    let source = PendingTemplates {
+        source,
+        templates: std::slice::from_ref(template),
+        removed_ids: &[],
+    };
+    check_pending(template, &source)
+}
+
+/// 检查一批同时写入的模板中的章节引用，模板之间可以互相引用，`removed_ids` 中的模板视为已删除
+///
+/// 错误路径以 `[序号]` 开头。
+pub fn check_batch_references(
+    templates: &[Template],
+    removed_ids: &[String],
+    source: &dyn ReferenceSource,
+) -> Vec<FieldError> {
+    let source = PendingTemplates {
+        source,
+        templates,
+        removed_ids,
+    };
+    templates
+        .iter()
+        .enumerate()
+        .flat_map(|(index, template)| {
+            let prefix = format!("[{}]", index);
+            check_pending(template, &source)
+                .into_iter()
+                .map(move |error| error.prefixed(&prefix))
+        })
+        .collect()
+}
+
+fn check_pending(template: &Template, source: &PendingTemplates) -> Vec<FieldError> {
+    template
+        .sections
+        .iter()
+        .enumerate()
+        .filter(|(_, section)| section.reference.is_some())
+        .filter_map(|(index, section)| {
+            resolve_section(template, section, source)
+                .err()
+                .map(|message| FieldError {
+                    field: format!("sections[{}].reference", index),
+                    message,
+                })
+        })
+        .collect()
+}
+
+/// 列出直接或间接依赖指定引用目标（章节或常用短语）的所有章节，按引用层级排序
+pub fn dependents(templates: &[Template], target: &SectionReference) -> Vec<SectionDependent> {
+    let mut dependents = Vec::new();
+    let mut seen = HashSet::new();
+    if let SectionReference::Section {
+        template_id,
+        section_id,
+    } = target
+    {
+        seen.insert((template_id.clone(), section_id.clone()));
+    }
+    let mut queue = VecDeque::from([(target.clone(), 0)]);
+
+    while let Some((target, depth)) = queue.pop_front() {
+        for template in templates {
+            for section in &template.sections {
+                if section.reference.as_ref() != Some(&target) {
+                    continue;
+                }
+
+                let key = (template.id.clone(), section.id.clone().unwrap_or_default());
+                if seen.insert(key.clone()) {
+                    dependents.push(SectionDependent {
+                        template_id: key.0.clone(),
+                        template_title: template.title.clone(),
+                        section_id: key.1.clone(),
+                        section_title: section.title.clone(),
+                        depth: depth + 1,
+                    });
+                    let next = SectionReference::Section {
+                        template_id: key.0,
+                        section_id: key.1,
+                    };
+                    queue.push_back((next, depth + 1));
+                }
+            }
+        }
+    }
+
+    dependents
+}
+
+/// 列出删除模板后引用会失效的章节：直接或间接引用了该模板任一章节的章节
+///
+/// `removed_ids` 为一起删除的模板（包括该模板本身），其中的章节不计入。
+pub fn deletion_dependents(
+    templates: &[Template],
+    template_id: &str,
+    removed_ids: &[String],
+) -> Vec<SectionDependent> {
+    let mut result: Vec<SectionDependent> = Vec::new();
+    let Some(template) = templates.iter().find(|template| template.id == template_id) else {
+        return result;
+    };
+    for section_id in template
+        .sections
+        .iter()
+        .filter_map(|section| section.id.as_ref())
+    {
+        let target = SectionReference::Section {
+            template_id: template_id.to_string(),
+            section_id: section_id.clone(),
+        };
+        for dependent in dependents(templates, &target) {
+            let counted = removed_ids.contains(&dependent.template_id)
+                || result.iter().any(|existing| {
+                    existing.template_id == dependent.template_id
+                        && existing.section_id == dependent.section_id
+                });
+            if !counted {
+                result.push(dependent);
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn section(id: &str, reference: Option<SectionReference>) -> TemplateSection {
+        TemplateSection {
+            id: Some(id.to_string()),
+            reference,
+            ..TemplateSection::for_test(id, "内容")
+        }
+    }
+
+    fn template(id: &str, sections: Vec<TemplateSection>) -> Template {
+        Template {
+            id: id.to_string(),
+            ..Template::for_test(id, sections)
+        }
+    }
+
+    fn reference(template_id: &str, section_id: &str) -> Option<SectionReference> {
+        Some(SectionReference::Section {
+            template_id: template_id.to_string(),
+            section_id: section_id.to_string(),
+        })
+    }
+
+    #[test]
+    fn deletion_reports_direct_and_indirect_references() {
+        // c 引用 b，b 引用 a
+        let templates = vec![
+            template("a", vec![section("a1", None)]),
+            template("b", vec![section("b1", reference("a", "a1"))]),
+            template("c", vec![section("c1", reference("b", "b1"))]),
+        ];
+        let ids = |dependents: Vec<SectionDependent>| -> Vec<String> {
+            dependents
+                .into_iter()
+                .map(|dependent| dependent.section_id)
+                .collect()
+        };
+
+        assert_eq!(
+            ids(deletion_dependents(&templates, "a", &["a".to_string()])),
+            ["b1", "c1"]
+        );
+        // 与引用它的模板一起删除时只剩间接引用
+        let removed = ["a".to_string(), "b".to_string()];
+        assert_eq!(ids(deletion_dependents(&templates, "a", &removed)), ["c1"]);
+        assert!(deletion_dependents(&templates, "c", &["c".to_string()]).is_empty());
+    }
+}
@@ -1,7 +1,14 @@
+use crate::composition;
+use crate::float::{FloatWindowState, DEFAULT_FLOAT_HOTKEY};
+use crate::format::{FormatProfile, CONTENT_ONLY_PROFILE_ID, DEFAULT_PROFILE_ID, MAX_INDENT};
+use crate::input::{AppPasteProfile, PasteStrategy};
+use crate::platform::history::WindowExclusionRules;
+use crate::suggestion::SuggestionRule;
+use crate::validation::{self, FieldError, ValidationRules};
 use duckdb::{Connection, Result as DuckResult, ToSql};
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use tauri::{AppHandle, Manager};
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 use uuid::Uuid;
 use rand::Rng;
 
@@ -18,14 +25,236 @@ pub struct Template {
     pub created_at: i64,
     pub updated_at: i64,
     pub is_favorite: bool,
+    /// 版本号，每次写入递增，用于检测并发修改
+    #[serde(default)]
+    pub version: i64,
 }
 
 /// 模板章节数据结构
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct TemplateSection {
+    /// 章节ID，在模板内唯一，保存时由后端分配
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     pub title: String,
     pub content: String,
+    /// 引用的内容，设置后渲染和粘贴时使用被引用的内容代替 `content`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reference: Option<SectionReference>,
+}
+
+#[cfg(test)]
+impl Template {
+    /// 测试用的模板：空ID、空分类、没有标签，其他字段按需用结构体更新语法覆盖
+    pub fn for_test(title: &str, sections: Vec<TemplateSection>) -> Template {
+        Template {
+            id: String::new(),
+            title: title.to_string(),
+            sections,
+            disease: String::new(),
+            template_type: String::new(),
+            tags: Vec::new(),
+            created_at: 0,
+            updated_at: 0,
+            is_favorite: false,
+            version: 1,
+        }
+    }
+}
+
+#[cfg(test)]
+impl TemplateSection {
+    /// 测试用的章节：没有ID和引用
+    pub fn for_test(title: &str, content: &str) -> TemplateSection {
+        TemplateSection {
+            id: None,
+            title: title.to_string(),
+            content: content.to_string(),
+            reference: None,
+        }
+    }
+}
+
+/// 章节引用
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SectionReference {
+    /// 引用其他模板的章节
+    #[serde(rename_all = "camelCase")]
+    Section {
+        template_id: String,
+        section_id: String,
+    },
+    /// 引用常用短语
+    #[serde(rename_all = "camelCase")]
+    Snippet { snippet_id: String },
+}
+
+/// 拆分模板时的章节范围（左闭右开）
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionRange {
+    pub start: usize,
+    pub end: usize,
+    pub title: Option<String>,
+}
+
+/// 合并模板时引用的章节
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionRef {
+    pub template_id: String,
+    pub section_index: usize,
+}
+
+/// 生成新的模板ID
+pub fn new_template_id() -> String {
+    Uuid::now_v7().to_string()
+}
+
+/// 生成新的章节ID
+pub fn new_section_id() -> String {
+    Uuid::now_v7().to_string()
+}
+
+/// 是否为前端生成的旧模板ID（`template-<时间戳>` 或 `template_<时间戳>`）
+fn is_legacy_template_id(id: &str) -> bool {
+    id.strip_prefix("template-")
+        .or_else(|| id.strip_prefix("template_"))
+        .is_some_and(|ts| !ts.is_empty() && ts.chars().all(|c| c.is_ascii_digit()))
+}
+
+impl Template {
+    /// 为缺少ID或ID重复的章节分配新的ID
+    pub fn assign_section_ids(&mut self) {
+        let mut seen = HashSet::new();
+        for section in &mut self.sections {
+            let unique = match &section.id {
+                Some(id) => !id.trim().is_empty() && seen.insert(id.clone()),
+                None => false,
+            };
+            if !unique {
+                let id = new_section_id();
+                seen.insert(id.clone());
+                section.id = Some(id);
+            }
+        }
+    }
+
+    /// 复制模板：新的ID、新的时间戳，标题追加“副本”
+    pub fn duplicate(&self) -> Template {
+        let now = chrono::Utc::now().timestamp_millis();
+        Template {
+            id: new_template_id(),
+            title: format!("{} - 副本", self.title),
+            created_at: now,
+            updated_at: now,
+            is_favorite: false,
+            version: 1,
+            ..self.clone()
+        }
+    }
+
+    /// 按章节范围把模板拆分为多个新模板
+    pub fn split(&self, ranges: &[SectionRange]) -> Result<Vec<Template>, String> {
+        if ranges.is_empty() {
+            return Err("No section ranges given".to_string());
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut templates = Vec::with_capacity(ranges.len());
+        for (index, range) in ranges.iter().enumerate() {
+            if range.start >= range.end || range.end > self.sections.len() {
+                return Err(format!(
+                    "Invalid section range {}..{} for template with {} sections",
+                    range.start,
+                    range.end,
+                    self.sections.len()
+                ));
+            }
+
+            let title = match &range.title {
+                Some(title) if !title.trim().is_empty() => title.clone(),
+                _ => format!("{} ({})", self.title, index + 1),
+            };
+            templates.push(Template {
+                id: new_template_id(),
+                title,
+                sections: self.sections[range.start..range.end].to_vec(),
+                created_at: now,
+                updated_at: now,
+                is_favorite: false,
+                version: 1,
+                ..self.clone()
+            });
+        }
+
+        Ok(templates)
+    }
+
+    /// 按给定章节顺序把多个模板合并为一个新模板
+    ///
+    /// 疾病和模板类型取第一个模板，标签取并集；`order` 为空时按模板顺序拼接全部章节。
+    pub fn merge(
+        templates: &[Template],
+        order: &[SectionRef],
+        title: &str,
+    ) -> Result<Template, String> {
+        let first = templates.first().ok_or("No templates to merge")?;
+
+        let sections = if order.is_empty() {
+            templates
+                .iter()
+                .flat_map(|template| template.sections.iter().cloned())
+                .collect()
+        } else {
+            let mut sections = Vec::with_capacity(order.len());
+            for section_ref in order {
+                let template = templates
+                    .iter()
+                    .find(|template| template.id == section_ref.template_id)
+                    .ok_or_else(|| {
+                        format!("Template {} is not being merged", section_ref.template_id)
+                    })?;
+                let section = template
+                    .sections
+                    .get(section_ref.section_index)
+                    .ok_or_else(|| {
+                        format!(
+                            "Section {} not found in template {}",
+                            section_ref.section_index, section_ref.template_id
+                        )
+                    })?;
+                sections.push(section.clone());
+            }
+            sections
+        };
+
+        let mut tags: Vec<String> = Vec::new();
+        for tag in templates.iter().flat_map(|template| template.tags.iter()) {
+            if !tags.contains(tag) {
+                tags.push(tag.clone());
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let mut merged = Template {
+            id: new_template_id(),
+            title: title.to_string(),
+            sections,
+            disease: first.disease.clone(),
+            template_type: first.template_type.clone(),
+            tags,
+            created_at: now,
+            updated_at: now,
+            is_favorite: false,
+            version: 1,
+        };
+        // 来自不同模板或重复选择的章节可能ID相同
+        merged.assign_section_ids();
+        Ok(merged)
+    }
 }
 
 /// 标签数据结构
@@ -53,6 +282,140 @@ pub struct TemplateType {
     pub template_count: i32,
 }
 
+/// 常用短语数据结构
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Snippet {
+    pub id: String,
+    pub content: String,
+    pub category: String,
+    /// 拼音检索词（全拼和首字母，以空格分隔），由前端生成，保存时只保留字母、数字和空格
+    #[serde(default)]
+    pub pinyin: String,
+    #[serde(default)]
+    pub usage_count: i64,
+    #[serde(default)]
+    pub created_at: i64,
+    #[serde(default)]
+    pub updated_at: i64,
+}
+
+/// 常用短语分类数据结构
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SnippetCategory {
+    pub name: String,
+    pub snippet_count: i32,
+}
+
+/// 保存模板或常用短语失败的原因
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum SaveError {
+    /// 模板已被其他窗口修改，携带服务端当前的模板
+    Conflict { current: Template },
+    /// 内容未通过校验，按字段路径列出错误
+    Validation { errors: Vec<FieldError> },
+    /// 数据库错误
+    Database { message: String },
+}
+
+impl From<duckdb::Error> for SaveError {
+    fn from(e: duckdb::Error) -> Self {
+        SaveError::Database {
+            message: format!("Database error: {}", e),
+        }
+    }
+}
+
+impl From<String> for SaveError {
+    fn from(message: String) -> Self {
+        SaveError::Database { message }
+    }
+}
+
+impl From<&str> for SaveError {
+    fn from(message: &str) -> Self {
+        SaveError::Database {
+            message: message.to_string(),
+        }
+    }
+}
+
+/// 删除模板或常用短语失败的原因
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum DeleteError {
+    /// 仍被其他模板的章节引用，删除后这些章节将无法渲染
+    Referenced {
+        dependents: Vec<composition::SectionDependent>,
+    },
+    /// 数据库错误
+    Database { message: String },
+}
+
+impl From<duckdb::Error> for DeleteError {
+    fn from(e: duckdb::Error) -> Self {
+        DeleteError::Database {
+            message: format!("Database error: {}", e),
+        }
+    }
+}
+
+impl From<String> for DeleteError {
+    fn from(message: String) -> Self {
+        DeleteError::Database { message }
+    }
+}
+
+impl From<&str> for DeleteError {
+    fn from(message: &str) -> Self {
+        DeleteError::Database {
+            message: message.to_string(),
+        }
+    }
+}
+
+/// 批量操作中单个模板的执行结果
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BulkOperationResult {
+    pub id: String,
+    pub success: bool,
+    pub error: Option<String>,
+}
+
+impl BulkOperationResult {
+    fn failed(id: &str, error: String) -> Self {
+        BulkOperationResult {
+            id: id.to_string(),
+            success: false,
+            error: Some(error),
+        }
+    }
+
+    fn from_affected(id: &str, affected: usize) -> Self {
+        BulkOperationResult {
+            id: id.to_string(),
+            success: affected > 0,
+            error: if affected > 0 {
+                None
+            } else {
+                Some("Template not found".to_string())
+            },
+        }
+    }
+}
+
+/// 描述引用了被删除内容的章节，用于批量删除的执行结果
+fn describe_dependents(dependents: &[composition::SectionDependent]) -> String {
+    let sections: Vec<String> = dependents
+        .iter()
+        .map(|dependent| format!("{} / {}", dependent.template_title, dependent.section_title))
+        .collect();
+    format!("仍被以下章节引用：{}", sections.join("、"))
+}
+
 /// 数据库管理器
 pub struct DatabaseManager {
     conn: Connection,
@@ -92,7 +455,8 @@ impl DatabaseManager {
                 tags JSON NOT NULL,
                 created_at BIGINT NOT NULL,
                 updated_at BIGINT NOT NULL,
-                is_favorite BOOLEAN NOT NULL DEFAULT FALSE
+                is_favorite BOOLEAN NOT NULL DEFAULT FALSE,
+                version BIGINT NOT NULL DEFAULT 1
             )
             "#,
             [],
@@ -110,22 +474,296 @@ impl DatabaseManager {
             [],
         )?;
 
+        // 创建常用短语表
+        self.conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS snippets (
+                id VARCHAR PRIMARY KEY,
+                content VARCHAR NOT NULL,
+                category VARCHAR NOT NULL,
+                pinyin VARCHAR NOT NULL,
+                usage_count BIGINT NOT NULL DEFAULT 0,
+                created_at BIGINT NOT NULL,
+                updated_at BIGINT NOT NULL
+            )
+            "#,
+            [],
+        )?;
+
+        // 创建设置表
+        self.conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS settings (
+                key VARCHAR PRIMARY KEY,
+                value JSON NOT NULL
+            )
+            "#,
+            [],
+        )?;
+
+        // 创建迁移记录表
+        self.conn.execute(
+            r#"
+            CREATE TABLE IF NOT EXISTS migrations (
+                name VARCHAR PRIMARY KEY,
+                applied_at BIGINT NOT NULL
+            )
+            "#,
+            [],
+        )?;
+
+        self.run_migration("template_version", |manager| {
+            manager.conn.execute(
+                "ALTER TABLE templates ADD COLUMN IF NOT EXISTS version BIGINT DEFAULT 1",
+                [],
+            )?;
+            Ok(())
+        })?;
+        self.run_migration("legacy_template_ids", Self::migrate_legacy_template_ids)?;
+        self.run_migration("section_ids", |manager| {
+            let mut templates = manager.get_all_templates()?;
+            for template in &mut templates {
+                template.assign_section_ids();
+            }
+            manager.write_templates(&templates, &[])
+        })?;
+
         Ok(())
     }
 
+    /// 执行一次性迁移，已执行过的迁移会被跳过
+    fn run_migration(
+        &mut self,
+        name: &str,
+        migrate: fn(&mut Self) -> DuckResult<()>,
+    ) -> DuckResult<()> {
+        let applied: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM migrations WHERE name = ?",
+            [name],
+            |row| row.get(0),
+        )?;
+        if applied > 0 {
+            return Ok(());
+        }
+
+        migrate(self)?;
+
+        self.conn.execute(
+            "INSERT INTO migrations (name, applied_at) VALUES (?, ?)",
+            [
+                &name as &dyn ToSql,
+                &chrono::Utc::now().timestamp_millis() as &dyn ToSql,
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// 将前端生成的旧模板ID迁移为 UUID v7
+    fn migrate_legacy_template_ids(&mut self) -> DuckResult<()> {
+        let legacy: Vec<Template> = self
+            .get_all_templates()?
+            .into_iter()
+            .filter(|template| is_legacy_template_id(&template.id))
+            .collect();
+        if legacy.is_empty() {
+            return Ok(());
+        }
+
+        let remove_ids: Vec<String> = legacy.iter().map(|template| template.id.clone()).collect();
+        let migrated: Vec<Template> = legacy
+            .into_iter()
+            .map(|template| Template {
+                id: new_template_id(),
+                ..template
+            })
+            .collect();
+        log::info!("迁移 {} 个旧模板ID", migrated.len());
+        self.write_templates(&migrated, &remove_ids)
+    }
+
+    /// 读取设置项，不存在时返回 `None`
+    pub fn get_setting<T: DeserializeOwned>(&self, key: &str) -> DuckResult<Option<T>> {
+        let mut stmt = self.conn.prepare("SELECT value FROM settings WHERE key = ?")?;
+        let mut rows = stmt.query_map([key], |row| {
+            let value_json: String = row.get(0)?;
+            serde_json::from_str::<T>(&value_json)
+                .map_err(|_| duckdb::Error::InvalidColumnIndex(0))
+        })?;
+
+        match rows.next() {
+            Some(value) => Ok(Some(value?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 保存设置项
+    pub fn set_setting<T: Serialize>(&mut self, key: &str, value: &T) -> DuckResult<()> {
+        let value_json = serde_json::to_string(value)
+            .map_err(|e| duckdb::Error::ToSqlConversionFailure(Box::new(e)))?;
+        self.conn.execute(
+            "INSERT OR REPLACE INTO settings (key, value) VALUES (?, ?)",
+            [key, value_json.as_str()],
+        )?;
+        Ok(())
+    }
+
+    /// 获取模板校验规则
+    pub fn get_validation_rules(&self) -> DuckResult<ValidationRules> {
+        Ok(self.get_setting("validation_rules")?.unwrap_or_default())
+    }
+
+    /// 保存模板校验规则
+    pub fn set_validation_rules(&mut self, rules: &ValidationRules) -> DuckResult<()> {
+        self.set_setting("validation_rules", rules)
+    }
+
+    /// 获取默认的粘贴方式
+    pub fn get_paste_strategy(&self) -> DuckResult<PasteStrategy> {
+        Ok(self.get_setting("paste_strategy")?.unwrap_or_default())
+    }
+
+    /// 保存默认的粘贴方式
+    pub fn set_paste_strategy(&mut self, strategy: &PasteStrategy) -> DuckResult<()> {
+        self.set_setting("paste_strategy", strategy)
+    }
+
+    /// 获取窗口排除规则
+    pub fn get_window_exclusion_rules(&self) -> DuckResult<WindowExclusionRules> {
+        Ok(self.get_setting("window_exclusion_rules")?.unwrap_or_default())
+    }
+
+    /// 保存窗口排除规则
+    pub fn set_window_exclusion_rules(&mut self, rules: &WindowExclusionRules) -> DuckResult<()> {
+        self.set_setting("window_exclusion_rules", rules)
+    }
+
+    /// 获取所有格式方案，默认方案总是排在第一位
+    pub fn get_format_profiles(&self) -> DuckResult<Vec<FormatProfile>> {
+        let mut profiles: Vec<FormatProfile> =
+            self.get_setting("format_profiles")?.unwrap_or_default();
+        if !profiles.iter().any(|profile| profile.id == DEFAULT_PROFILE_ID) {
+            profiles.insert(0, FormatProfile::default());
+        }
+        Ok(profiles)
+    }
+
+    /// 根据ID获取格式方案，包括内置的只输出内容的方案
+    pub fn get_format_profile(&self, id: &str) -> DuckResult<Option<FormatProfile>> {
+        if id == CONTENT_ONLY_PROFILE_ID {
+            return Ok(Some(FormatProfile::content_only()));
+        }
+        Ok(self
+            .get_format_profiles()?
+            .into_iter()
+            .find(|profile| profile.id == id))
+    }
+
+    /// 保存格式方案，ID为空时新建，返回保存后的方案
+    pub fn save_format_profile(&mut self, mut profile: FormatProfile) -> DuckResult<FormatProfile> {
+        if profile.id.is_empty() || profile.id == CONTENT_ONLY_PROFILE_ID {
+            profile.id = format!("profile-{}", Uuid::now_v7());
+        }
+        profile.indent = profile.indent.min(MAX_INDENT);
+
+        let mut profiles = self.get_format_profiles()?;
+        match profiles.iter_mut().find(|existing| existing.id == profile.id) {
+            Some(existing) => *existing = profile.clone(),
+            None => profiles.push(profile.clone()),
+        }
+        self.set_setting("format_profiles", &profiles)?;
+        Ok(profile)
+    }
+
+    /// 删除格式方案
+    pub fn delete_format_profile(&mut self, id: &str) -> DuckResult<()> {
+        let mut profiles = self.get_format_profiles()?;
+        profiles.retain(|profile| profile.id != id);
+        self.set_setting("format_profiles", &profiles)
+    }
+
+    /// 获取呼出悬浮窗口的快捷键，为空时表示不使用快捷键
+    pub fn get_float_hotkey(&self) -> DuckResult<String> {
+        Ok(self
+            .get_setting("float_hotkey")?
+            .unwrap_or_else(|| DEFAULT_FLOAT_HOTKEY.to_string()))
+    }
+
+    /// 保存呼出悬浮窗口的快捷键
+    pub fn set_float_hotkey(&mut self, hotkey: &str) -> DuckResult<()> {
+        self.set_setting("float_hotkey", &hotkey)
+    }
+
+    /// 获取指定显示器布局下悬浮窗口的状态
+    pub fn get_float_window_state(&self, layout: &str) -> DuckResult<Option<FloatWindowState>> {
+        let mut states: HashMap<String, FloatWindowState> =
+            self.get_setting("float_window_states")?.unwrap_or_default();
+        Ok(states.remove(layout))
+    }
+
+    /// 保存指定显示器布局下悬浮窗口的状态
+    pub fn set_float_window_state(
+        &mut self,
+        layout: &str,
+        state: &FloatWindowState,
+    ) -> DuckResult<()> {
+        let mut states: HashMap<String, FloatWindowState> =
+            self.get_setting("float_window_states")?.unwrap_or_default();
+        states.insert(layout.to_string(), *state);
+        self.set_setting("float_window_states", &states)
+    }
+
+    /// 获取模板推荐规则
+    pub fn get_suggestion_rules(&self) -> DuckResult<Vec<SuggestionRule>> {
+        Ok(self.get_setting("suggestion_rules")?.unwrap_or_default())
+    }
+
+    /// 保存模板推荐规则
+    pub fn set_suggestion_rules(&mut self, rules: &[SuggestionRule]) -> DuckResult<()> {
+        self.set_setting("suggestion_rules", &rules)
+    }
+
+    /// 获取所有程序粘贴方案，按匹配的优先顺序排列
+    pub fn get_app_paste_profiles(&self) -> DuckResult<Vec<AppPasteProfile>> {
+        Ok(self.get_setting("app_paste_profiles")?.unwrap_or_default())
+    }
+
+    /// 保存程序粘贴方案，ID为空时新建并排在最后，返回保存后的方案
+    pub fn save_app_paste_profile(
+        &mut self,
+        mut profile: AppPasteProfile,
+    ) -> DuckResult<AppPasteProfile> {
+        if profile.id.is_empty() {
+            profile.id = format!("app-profile-{}", Uuid::now_v7());
+        }
+
+        let mut profiles = self.get_app_paste_profiles()?;
+        match profiles.iter_mut().find(|existing| existing.id == profile.id) {
+            Some(existing) => *existing = profile.clone(),
+            None => profiles.push(profile.clone()),
+        }
+        self.set_setting("app_paste_profiles", &profiles)?;
+        Ok(profile)
+    }
+
+    /// 删除程序粘贴方案
+    pub fn delete_app_paste_profile(&mut self, id: &str) -> DuckResult<()> {
+        let mut profiles = self.get_app_paste_profiles()?;
+        profiles.retain(|profile| profile.id != id);
+        self.set_setting("app_paste_profiles", &profiles)
+    }
+
     /// 插入或更新模板
     pub fn upsert_template(&mut self, template: &Template) -> DuckResult<()> {
         let sections_json = serde_json::to_string(&template.sections)
             .map_err(|e| duckdb::Error::ToSqlConversionFailure(Box::new(e)))?;
         let tags_json = serde_json::to_string(&template.tags)
             .map_err(|e| duckdb::Error::ToSqlConversionFailure(Box::new(e)))?;
-        println!("upsert_template:{:?}", template);
 
         self.conn.execute(
             r#"
             INSERT OR REPLACE INTO templates 
-            (id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            (id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite, version)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
             [
                 &template.id as &dyn ToSql,
@@ -137,16 +775,134 @@ impl DatabaseManager {
                 &template.created_at as &dyn ToSql,
                 &template.updated_at as &dyn ToSql,
                 &template.is_favorite as &dyn ToSql,
+                &template.version as &dyn ToSql,
             ],
         )?;
 
         Ok(())
     }
 
+    /// 保存模板：由后端分配ID、时间戳和版本号，返回保存后的模板
+    ///
+    /// ID为空或数据库中不存在时分配新的 UUID v7；更新时保留原有的创建时间，
+    /// 若提交的版本号与数据库中的不一致则返回冲突错误，不覆盖数据。
+    pub fn save_template(
+        &mut self,
+        mut template: Template,
+    ) -> Result<Template, SaveError> {
+        validation::normalize_template(&mut template);
+        template.assign_section_ids();
+        let mut errors = validation::validate_template(&template, &self.get_validation_rules()?);
+        errors.extend(self.check_references(&template));
+        if !errors.is_empty() {
+            return Err(SaveError::Validation { errors });
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let existing = if template.id.trim().is_empty() {
+            None
+        } else {
+            self.get_template_by_id(&template.id)?
+        };
+
+        match existing {
+            Some(existing) => {
+                if template.version != existing.version {
+                    return Err(SaveError::Conflict { current: existing });
+                }
+                template.created_at = existing.created_at;
+                template.version = existing.version + 1;
+            }
+            None => {
+                template.id = new_template_id();
+                template.created_at = now;
+                template.version = 1;
+            }
+        }
+        template.updated_at = now;
+
+        self.upsert_template(&template)?;
+        Ok(template)
+    }
+
+    /// 导入模板：全部通过校验后才批量写入，错误路径以 `[序号]` 开头
+    pub fn import_templates(
+        &mut self,
+        mut templates: Vec<Template>,
+    ) -> Result<(), SaveError> {
+        self.check_templates(&mut templates, &[])?;
+
+        // 导入数据中的版本号不可信：覆盖已有模板时在其版本上加一，新模板从 1 开始
+        for template in &mut templates {
+            template.version = match self.get_template_by_id(&template.id)? {
+                Some(existing) => existing.version + 1,
+                None => 1,
+            };
+        }
+
+        self.batch_upsert_templates(&templates)?;
+        Ok(())
+    }
+
+    /// 解析模板中的章节引用，返回用被引用内容替换后的模板
+    pub fn resolve_template(&self, template: &Template) -> Result<Template, String> {
+        composition::resolve_sections(template, self)
+    }
+
+    /// 检查待保存模板的章节引用能否解析（目标存在且没有循环）
+    fn check_references(&self, template: &Template) -> Vec<FieldError> {
+        composition::check_references(template, self)
+    }
+
+    /// 规范化并校验一批同时写入的模板，包括章节ID和章节引用，错误路径以 `[序号]` 开头
+    ///
+    /// `remove_ids` 为同一批写入中将被删除的模板，引用它们的章节视为引用不存在。
+    fn check_templates(
+        &self,
+        templates: &mut [Template],
+        remove_ids: &[String],
+    ) -> Result<(), SaveError> {
+        let rules = self.get_validation_rules()?;
+        let mut errors = Vec::new();
+        for (index, template) in templates.iter_mut().enumerate() {
+            validation::normalize_template(template);
+            template.assign_section_ids();
+            let prefix = format!("[{}]", index);
+            errors.extend(
+                validation::validate_template(template, &rules)
+                    .into_iter()
+                    .map(|error| error.prefixed(&prefix)),
+            );
+        }
+        errors.extend(composition::check_batch_references(
+            templates, remove_ids, self,
+        ));
+        if !errors.is_empty() {
+            return Err(SaveError::Validation { errors });
+        }
+        Ok(())
+    }
+
+    /// 列出直接或间接引用了指定章节的所有章节
+    pub fn get_section_dependents(
+        &self,
+        template_id: &str,
+        section_id: &str,
+    ) -> DuckResult<Vec<composition::SectionDependent>> {
+        let templates = self.get_all_templates()?;
+        Ok(composition::dependents(
+            &templates,
+            &SectionReference::Section {
+                template_id: template_id.to_string(),
+                section_id: section_id.to_string(),
+            },
+        ))
+    }
+
     /// 批量添加模板
     pub fn batch_upsert_templates(&mut self, templates: &[Template]) -> DuckResult<()> {
         let mut stmt = self.conn.prepare(
-            "INSERT OR REPLACE INTO templates (id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            "INSERT OR REPLACE INTO templates (id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite, version) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
         )?;
         for template in templates {
             let sections_json = serde_json::to_string(&template.sections)
@@ -163,15 +919,61 @@ impl DatabaseManager {
                 &template.created_at as &dyn ToSql,
                 &template.updated_at as &dyn ToSql,
                 &template.is_favorite as &dyn ToSql,
+                &template.version as &dyn ToSql,
             ])?;
         }
         Ok(())
     }
 
+    /// 在同一事务中保存派生出的模板并删除被替换的模板（用于拆分、合并）
+    ///
+    /// 与保存、导入相同，写入前校验模板并检查章节ID和章节引用。
+    pub fn replace_templates(
+        &mut self,
+        mut templates: Vec<Template>,
+        remove_ids: &[String],
+    ) -> Result<Vec<Template>, SaveError> {
+        self.check_templates(&mut templates, remove_ids)?;
+        self.write_templates(&templates, remove_ids)?;
+        Ok(templates)
+    }
+
+    /// 在同一事务中写入模板并删除指定模板，不做校验，供迁移使用
+    fn write_templates(&mut self, templates: &[Template], remove_ids: &[String]) -> DuckResult<()> {
+        let tx = self.conn.transaction()?;
+        for id in remove_ids {
+            tx.execute("DELETE FROM templates WHERE id = ?", [id])?;
+        }
+        {
+            let mut stmt = tx.prepare(
+                "INSERT OR REPLACE INTO templates (id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite, version) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            )?;
+            for template in templates {
+                let sections_json = serde_json::to_string(&template.sections)
+                    .map_err(|e| duckdb::Error::ToSqlConversionFailure(Box::new(e)))?;
+                let tags_json = serde_json::to_string(&template.tags)
+                    .map_err(|e| duckdb::Error::ToSqlConversionFailure(Box::new(e)))?;
+                stmt.execute([
+                    &template.id as &dyn ToSql,
+                    &template.title as &dyn ToSql,
+                    &sections_json as &dyn ToSql,
+                    &template.disease as &dyn ToSql,
+                    &template.template_type as &dyn ToSql,
+                    &tags_json as &dyn ToSql,
+                    &template.created_at as &dyn ToSql,
+                    &template.updated_at as &dyn ToSql,
+                    &template.is_favorite as &dyn ToSql,
+                    &template.version as &dyn ToSql,
+                ])?;
+            }
+        }
+        tx.commit()
+    }
+
     /// 获取所有模板
     pub fn get_all_templates(&self) -> DuckResult<Vec<Template>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite FROM templates ORDER BY updated_at DESC"
+            "SELECT id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite, version FROM templates ORDER BY updated_at DESC"
         )?;
 
         let template_iter = stmt.query_map([], |row| {
@@ -193,6 +995,7 @@ impl DatabaseManager {
                 created_at: row.get::<_, i64>(6)?,
                 updated_at: row.get::<_, i64>(7)?,
                 is_favorite: row.get::<_, bool>(8)?,
+                version: row.get::<_, i64>(9)?,
             })
         })?;
 
@@ -207,7 +1010,7 @@ impl DatabaseManager {
     /// 根据ID获取模板
     pub fn get_template_by_id(&self, id: &str) -> DuckResult<Option<Template>> {
         let mut stmt = self.conn.prepare(
-            "SELECT id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite FROM templates WHERE id = ?"
+            "SELECT id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite, version FROM templates WHERE id = ?"
         )?;
 
         let mut rows = stmt.query_map([id], |row| {
@@ -229,6 +1032,7 @@ impl DatabaseManager {
                 created_at: row.get::<_, i64>(6)?,
                 updated_at: row.get::<_, i64>(7)?,
                 is_favorite: row.get::<_, bool>(8)?,
+                version: row.get::<_, i64>(9)?,
             })
         })?;
 
@@ -239,7 +1043,14 @@ impl DatabaseManager {
     }
 
     /// 删除模板
-    pub fn delete_template(&mut self, id: &str) -> DuckResult<()> {
+    ///
+    /// 模板的章节仍被其他模板引用时不删除，返回受影响的章节。
+    pub fn delete_template(&mut self, id: &str) -> Result<(), DeleteError> {
+        let templates = self.get_all_templates()?;
+        let dependents = composition::deletion_dependents(&templates, id, &[id.to_string()]);
+        if !dependents.is_empty() {
+            return Err(DeleteError::Referenced { dependents });
+        }
         self.conn
             .execute("DELETE FROM templates WHERE id = ?", [id])?;
         Ok(())
@@ -248,7 +1059,7 @@ impl DatabaseManager {
     /// 切换模板收藏状态
     pub fn toggle_template_favorite(&mut self, id: &str) -> DuckResult<()> {
         self.conn.execute(
-            "UPDATE templates SET is_favorite = NOT is_favorite, updated_at = ? WHERE id = ?",
+            "UPDATE templates SET is_favorite = NOT is_favorite, updated_at = ?, version = version + 1 WHERE id = ?",
             [
                 &chrono::Utc::now().timestamp_millis() as &dyn ToSql,
                 &id as &dyn ToSql,
@@ -257,6 +1068,408 @@ impl DatabaseManager {
         Ok(())
     }
 
+    /// 在同一事务中对多个模板执行操作，返回每个模板的执行结果
+    ///
+    /// `op` 返回受影响的行数，为 0 时视为模板不存在；数据库错误会回滚整个事务。
+    fn bulk_apply<F>(
+        &mut self,
+        ids: &[String],
+        mut op: F,
+    ) -> DuckResult<Vec<BulkOperationResult>>
+    where
+        F: FnMut(&Connection, &str) -> DuckResult<usize>,
+    {
+        let tx = self.conn.transaction()?;
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            let affected = op(&tx, id)?;
+            results.push(BulkOperationResult::from_affected(id, affected));
+        }
+        tx.commit()?;
+        Ok(results)
+    }
+
+    /// 在同一事务中修改多个模板的标签列表
+    ///
+    /// `update` 返回错误时不修改该模板，错误记录在该模板的执行结果中。
+    fn bulk_update_tags<F>(
+        &mut self,
+        ids: &[String],
+        mut update: F,
+    ) -> DuckResult<Vec<BulkOperationResult>>
+    where
+        F: FnMut(&mut Vec<String>) -> Result<(), String>,
+    {
+        let now = chrono::Utc::now().timestamp_millis();
+        let tx = self.conn.transaction()?;
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            let mut stmt = tx.prepare("SELECT tags FROM templates WHERE id = ?")?;
+            let mut rows = stmt.query_map([id], |row| {
+                let tags_json: String = row.get(0)?;
+                serde_json::from_str::<Vec<String>>(&tags_json)
+                    .map_err(|_| duckdb::Error::InvalidColumnIndex(0))
+            })?;
+            let mut tags = match rows.next() {
+                Some(tags) => tags?,
+                None => {
+                    results.push(BulkOperationResult::from_affected(id, 0));
+                    continue;
+                }
+            };
+
+            if let Err(error) = update(&mut tags) {
+                results.push(BulkOperationResult::failed(id, error));
+                continue;
+            }
+            let tags_json = serde_json::to_string(&tags)
+                .map_err(|e| duckdb::Error::ToSqlConversionFailure(Box::new(e)))?;
+            let affected = tx.execute(
+                "UPDATE templates SET tags = ?, updated_at = ?, version = version + 1 WHERE id = ?",
+                [
+                    &tags_json as &dyn ToSql,
+                    &now as &dyn ToSql,
+                    &id as &dyn ToSql,
+                ],
+            )?;
+            results.push(BulkOperationResult::from_affected(id, affected));
+        }
+        tx.commit()?;
+        Ok(results)
+    }
+
+    /// 批量删除模板
+    ///
+    /// 章节仍被其他模板引用的模板不删除，在其执行结果中列出引用它的章节；
+    /// 同一批删除的模板之间的引用不影响删除。
+    pub fn bulk_delete_templates(
+        &mut self,
+        ids: &[String],
+    ) -> DuckResult<Vec<BulkOperationResult>> {
+        // 被保留的模板可能引用了其他待删除的模板，反复排除直到没有新的被保留的模板
+        let templates = self.get_all_templates()?;
+        let mut removable = ids.to_vec();
+        let mut blocked = HashMap::new();
+        loop {
+            let newly_blocked: Vec<_> = removable
+                .iter()
+                .map(|id| {
+                    let dependents = composition::deletion_dependents(&templates, id, &removable);
+                    (id.clone(), dependents)
+                })
+                .filter(|(_, dependents)| !dependents.is_empty())
+                .collect();
+            if newly_blocked.is_empty() {
+                break;
+            }
+            removable.retain(|id| !newly_blocked.iter().any(|(blocked, _)| blocked == id));
+            blocked.extend(newly_blocked);
+        }
+
+        let tx = self.conn.transaction()?;
+        let mut results = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(dependents) = blocked.get(id) {
+                results.push(BulkOperationResult::failed(
+                    id,
+                    describe_dependents(dependents),
+                ));
+                continue;
+            }
+            let affected = tx.execute("DELETE FROM templates WHERE id = ?", [id])?;
+            results.push(BulkOperationResult::from_affected(id, affected));
+        }
+        tx.commit()?;
+        Ok(results)
+    }
+
+    /// 批量设置模板收藏状态
+    pub fn bulk_set_favorite(
+        &mut self,
+        ids: &[String],
+        is_favorite: bool,
+    ) -> DuckResult<Vec<BulkOperationResult>> {
+        let now = chrono::Utc::now().timestamp_millis();
+        self.bulk_apply(ids, |conn, id| {
+            conn.execute(
+                "UPDATE templates SET is_favorite = ?, updated_at = ?, version = version + 1 WHERE id = ?",
+                [&is_favorite as &dyn ToSql, &now as &dyn ToSql, &id as &dyn ToSql],
+            )
+        })
+    }
+
+    /// 规范化并校验批量设置的疾病或模板类型，与保存模板时的规则相同
+    fn check_category(&self, field: &str, category: &str) -> Result<String, SaveError> {
+        let category = validation::normalize_category(category);
+        let errors = validation::validate_category(field, &category, &self.get_validation_rules()?);
+        if !errors.is_empty() {
+            return Err(SaveError::Validation { errors });
+        }
+        Ok(category)
+    }
+
+    /// 批量设置模板疾病分类，为空时使用默认分类
+    pub fn bulk_set_disease(
+        &mut self,
+        ids: &[String],
+        disease: &str,
+    ) -> Result<Vec<BulkOperationResult>, SaveError> {
+        let disease = self.check_category("disease", disease)?;
+        let now = chrono::Utc::now().timestamp_millis();
+        Ok(self.bulk_apply(ids, |conn, id| {
+            conn.execute(
+                "UPDATE templates SET disease = ?, updated_at = ?, version = version + 1 WHERE id = ?",
+                [&disease as &dyn ToSql, &now as &dyn ToSql, &id as &dyn ToSql],
+            )
+        })?)
+    }
+
+    /// 批量设置模板类型，为空时使用默认分类
+    pub fn bulk_set_template_type(
+        &mut self,
+        ids: &[String],
+        template_type: &str,
+    ) -> Result<Vec<BulkOperationResult>, SaveError> {
+        let template_type = self.check_category("templateType", template_type)?;
+        let now = chrono::Utc::now().timestamp_millis();
+        Ok(self.bulk_apply(ids, |conn, id| {
+            conn.execute(
+                "UPDATE templates SET template_type = ?, updated_at = ?, version = version + 1 WHERE id = ?",
+                [&template_type as &dyn ToSql, &now as &dyn ToSql, &id as &dyn ToSql],
+            )
+        })?)
+    }
+
+    /// 批量为模板添加标签（已存在的标签不会重复添加）
+    ///
+    /// 标签本身不合法时不修改任何模板；添加后标签数量超过上限的模板不修改，在其执行结果中报告。
+    pub fn bulk_add_tags(
+        &mut self,
+        ids: &[String],
+        tags: &[String],
+    ) -> Result<Vec<BulkOperationResult>, SaveError> {
+        let rules = self.get_validation_rules()?;
+        let errors = validation::validate_tags(tags, &rules);
+        if !errors.is_empty() {
+            return Err(SaveError::Validation { errors });
+        }
+
+        Ok(self.bulk_update_tags(ids, |current| {
+            for tag in tags {
+                if !current.contains(tag) {
+                    current.push(tag.clone());
+                }
+            }
+            if current.len() > rules.max_tags {
+                return Err(format!("标签数量不能超过 {} 个", rules.max_tags));
+            }
+            Ok(())
+        })?)
+    }
+
+    /// 批量移除模板标签
+    pub fn bulk_remove_tags(
+        &mut self,
+        ids: &[String],
+        tags: &[String],
+    ) -> DuckResult<Vec<BulkOperationResult>> {
+        self.bulk_update_tags(ids, |current| {
+            current.retain(|tag| !tags.contains(tag));
+            Ok(())
+        })
+    }
+
+    /// 从查询结果行构造常用短语
+    fn snippet_from_row(row: &duckdb::Row) -> DuckResult<Snippet> {
+        Ok(Snippet {
+            id: row.get(0)?,
+            content: row.get(1)?,
+            category: row.get(2)?,
+            pinyin: row.get(3)?,
+            usage_count: row.get::<_, i64>(4)?,
+            created_at: row.get::<_, i64>(5)?,
+            updated_at: row.get::<_, i64>(6)?,
+        })
+    }
+
+    /// 获取所有常用短语，常用的排在前面
+    pub fn get_all_snippets(&self) -> DuckResult<Vec<Snippet>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, content, category, pinyin, usage_count, created_at, updated_at FROM snippets ORDER BY usage_count DESC, updated_at DESC"
+        )?;
+
+        let snippet_iter = stmt.query_map([], Self::snippet_from_row)?;
+
+        let mut snippets = Vec::new();
+        for snippet in snippet_iter {
+            snippets.push(snippet?);
+        }
+
+        Ok(snippets)
+    }
+
+    /// 根据ID获取常用短语
+    pub fn get_snippet_by_id(&self, id: &str) -> DuckResult<Option<Snippet>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, content, category, pinyin, usage_count, created_at, updated_at FROM snippets WHERE id = ?"
+        )?;
+
+        let mut rows = stmt.query_map([id], Self::snippet_from_row)?;
+
+        match rows.next() {
+            Some(snippet) => Ok(Some(snippet?)),
+            None => Ok(None),
+        }
+    }
+
+    /// 保存常用短语：由后端分配ID和时间戳，更新时保留创建时间和使用次数
+    pub fn save_snippet(&mut self, mut snippet: Snippet) -> Result<Snippet, SaveError> {
+        snippet.category = snippet.category.trim().to_string();
+        let errors = validation::validate_snippet(&snippet, &self.get_validation_rules()?);
+        if !errors.is_empty() {
+            return Err(SaveError::Validation { errors });
+        }
+
+        let now = chrono::Utc::now().timestamp_millis();
+        let existing = if snippet.id.trim().is_empty() {
+            None
+        } else {
+            self.get_snippet_by_id(&snippet.id)?
+        };
+
+        match existing {
+            Some(existing) => {
+                snippet.created_at = existing.created_at;
+                snippet.usage_count = existing.usage_count;
+            }
+            None => {
+                snippet.id = format!("snippet-{}", Uuid::now_v7());
+                snippet.created_at = now;
+                snippet.usage_count = 0;
+            }
+        }
+        snippet.updated_at = now;
+        if snippet.category.trim().is_empty() {
+            snippet.category = validation::DEFAULT_CATEGORY.to_string();
+        }
+        snippet.pinyin = snippet
+            .pinyin
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == ' ')
+            .collect::<String>()
+            .to_lowercase();
+
+        self.conn.execute(
+            r#"
+            INSERT OR REPLACE INTO snippets
+            (id, content, category, pinyin, usage_count, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+            [
+                &snippet.id as &dyn ToSql,
+                &snippet.content as &dyn ToSql,
+                &snippet.category as &dyn ToSql,
+                &snippet.pinyin as &dyn ToSql,
+                &snippet.usage_count as &dyn ToSql,
+                &snippet.created_at as &dyn ToSql,
+                &snippet.updated_at as &dyn ToSql,
+            ],
+        )?;
+
+        Ok(snippet)
+    }
+
+    /// 删除常用短语
+    ///
+    /// 仍被模板章节引用时不删除，返回受影响的章节。
+    pub fn delete_snippet(&mut self, id: &str) -> Result<(), DeleteError> {
+        let dependents = self.get_snippet_dependents(id)?;
+        if !dependents.is_empty() {
+            return Err(DeleteError::Referenced { dependents });
+        }
+        self.conn.execute("DELETE FROM snippets WHERE id = ?", [id])?;
+        Ok(())
+    }
+
+    /// 记录一次常用短语的使用
+    pub fn record_snippet_usage(&mut self, id: &str) -> DuckResult<()> {
+        self.conn.execute(
+            "UPDATE snippets SET usage_count = usage_count + 1 WHERE id = ?",
+            [id],
+        )?;
+        Ok(())
+    }
+
+    /// 搜索常用短语：匹配内容或拼音，可限定分类
+    pub fn search_snippets(
+        &self,
+        keyword: &str,
+        category: Option<&str>,
+    ) -> DuckResult<Vec<Snippet>> {
+        let search_pattern = format!("%{}%", keyword);
+        let pinyin_pattern = search_pattern.to_lowercase();
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, content, category, pinyin, usage_count, created_at, updated_at
+            FROM snippets
+            WHERE (content LIKE ? OR pinyin LIKE ?) AND (? IS NULL OR category = ?)
+            ORDER BY usage_count DESC, updated_at DESC
+            "#,
+        )?;
+
+        let snippet_iter = stmt.query_map(
+            [
+                &search_pattern as &dyn ToSql,
+                &pinyin_pattern as &dyn ToSql,
+                &category as &dyn ToSql,
+                &category as &dyn ToSql,
+            ],
+            Self::snippet_from_row,
+        )?;
+
+        let mut snippets = Vec::new();
+        for snippet in snippet_iter {
+            snippets.push(snippet?);
+        }
+
+        Ok(snippets)
+    }
+
+    /// 获取所有常用短语分类及其短语数量
+    pub fn get_all_snippet_categories(&self) -> DuckResult<Vec<SnippetCategory>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT category, COUNT(*) as snippet_count FROM snippets GROUP BY category ORDER BY category"
+        )?;
+
+        let category_iter = stmt.query_map([], |row| {
+            Ok(SnippetCategory {
+                name: row.get::<_, String>(0)?,
+                snippet_count: row.get::<_, i32>(1)?,
+            })
+        })?;
+
+        let mut categories = Vec::new();
+        for category in category_iter {
+            categories.push(category?);
+        }
+
+        Ok(categories)
+    }
+
+    /// 列出直接或间接引用了指定常用短语的所有章节
+    pub fn get_snippet_dependents(
+        &self,
+        snippet_id: &str,
+    ) -> DuckResult<Vec<composition::SectionDependent>> {
+        let templates = self.get_all_templates()?;
+        Ok(composition::dependents(
+            &templates,
+            &SectionReference::Snippet {
+                snippet_id: snippet_id.to_string(),
+            },
+        ))
+    }
+
     /// 获取所有疾病分类及其模板数量
     pub fn get_all_diseases(&self) -> DuckResult<Vec<Disease>> {
         let mut stmt = self.conn.prepare(
@@ -335,7 +1548,7 @@ impl DatabaseManager {
         let search_pattern = format!("%{}%", keyword);
         let mut stmt = self.conn.prepare(
             r#"
-            SELECT id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite 
+            SELECT id, title, sections, disease, template_type, tags, created_at, updated_at, is_favorite, version
             FROM templates 
             WHERE title LIKE ? OR sections LIKE ?
             ORDER BY updated_at DESC
@@ -361,6 +1574,7 @@ impl DatabaseManager {
                 created_at: row.get::<_, i64>(6)?,
                 updated_at: row.get::<_, i64>(7)?,
                 is_favorite: row.get::<_, bool>(8)?,
+                version: row.get::<_, i64>(9)?,
             })
         })?;
 
@@ -432,3 +1646,13 @@ impl DatabaseManager {
         Ok(())
     }
 }
+
+impl composition::ReferenceSource for DatabaseManager {
+    fn template(&self, id: &str) -> Option<Template> {
+        self.get_template_by_id(id).ok().flatten()
+    }
+
+    fn snippet(&self, id: &str) -> Option<Snippet> {
+        self.get_snippet_by_id(id).ok().flatten()
+    }
+}
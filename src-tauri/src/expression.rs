@@ -0,0 +1,636 @@
+use chrono::{Datelike, Local, NaiveDate};
+use std::fmt;
+
+/// 日期值接受的格式
+const DATE_FORMATS: &[&str] = &["%Y-%m-%d", "%Y/%m/%d", "%Y.%m.%d", "%Y年%m月%d日"];
+
+/// 表达式的值
+#[derive(Debug, Clone, PartialEq)]
+pub enum Value {
+    Null,
+    Number(f64),
+    Text(String),
+    Bool(bool),
+}
+
+impl Value {
+    /// 条件判断时的真值：空文本、0、未填写均为假
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Null => false,
+            Value::Number(n) => *n != 0.0,
+            Value::Text(text) => !text.trim().is_empty(),
+            Value::Bool(b) => *b,
+        }
+    }
+
+    fn as_number(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(*n),
+            Value::Text(text) => text.trim().parse().ok(),
+            _ => None,
+        }
+    }
+
+    fn as_date(&self) -> Option<NaiveDate> {
+        match self {
+            Value::Text(text) => parse_date(text.trim()),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Null => Ok(()),
+            Value::Number(n) => {
+                // 最多保留两位小数，并去掉末尾的 0
+                let text = format!("{:.2}", n);
+                let text = text.trim_end_matches('0').trim_end_matches('.');
+                f.write_str(if text == "-0" { "0" } else { text })
+            }
+            Value::Text(text) => f.write_str(text),
+            Value::Bool(b) => f.write_str(if *b { "是" } else { "否" }),
+        }
+    }
+}
+
+/// 解析日期文本
+pub fn parse_date(text: &str) -> Option<NaiveDate> {
+    DATE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text, format).ok())
+}
+
+/// 二元运算符
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum BinaryOp {
+    Or,
+    And,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Add,
+    Sub,
+    Mul,
+    Div,
+}
+
+/// 表达式语法树
+#[derive(Debug, Clone, PartialEq)]
+pub enum Expr {
+    Number(f64),
+    Text(String),
+    Variable(String),
+    Not(Box<Expr>),
+    Neg(Box<Expr>),
+    Binary(BinaryOp, Box<Expr>, Box<Expr>),
+    Call(String, Vec<Expr>),
+}
+
+/// 变量在表达式中的用途，用于推断表单字段类型
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum VariableUsage {
+    Text,
+    Number,
+    Date,
+}
+
+impl Expr {
+    /// 收集表达式中引用的变量及其用途，按首次出现的顺序
+    pub fn variables(&self) -> Vec<(String, VariableUsage)> {
+        let mut variables = Vec::new();
+        self.collect_variables(VariableUsage::Text, &mut variables);
+        variables
+    }
+
+    fn collect_variables(&self, usage: VariableUsage, out: &mut Vec<(String, VariableUsage)>) {
+        match self {
+            Expr::Number(_) | Expr::Text(_) => {}
+            Expr::Variable(name) => {
+                if out.iter().all(|(existing, _)| existing != name) {
+                    out.push((name.clone(), usage));
+                }
+            }
+            Expr::Not(inner) => inner.collect_variables(VariableUsage::Text, out),
+            Expr::Neg(inner) => inner.collect_variables(VariableUsage::Number, out),
+            Expr::Binary(op, left, right) => {
+                let usage = match op {
+                    BinaryOp::Or | BinaryOp::And | BinaryOp::Eq | BinaryOp::Ne => {
+                        VariableUsage::Text
+                    }
+                    _ => VariableUsage::Number,
+                };
+                left.collect_variables(usage, out);
+                right.collect_variables(usage, out);
+            }
+            Expr::Call(name, args) => {
+                for arg in args {
+                    arg.collect_variables(function_arg_usage(name), out);
+                }
+            }
+        }
+    }
+
+    /// 计算表达式，`lookup` 用于读取变量的值
+    pub fn eval(&self, lookup: &dyn Fn(&str) -> Value) -> Result<Value, String> {
+        match self {
+            Expr::Number(n) => Ok(Value::Number(*n)),
+            Expr::Text(text) => Ok(Value::Text(text.clone())),
+            Expr::Variable(name) => Ok(lookup(name)),
+            Expr::Not(inner) => Ok(Value::Bool(!inner.eval(lookup)?.is_truthy())),
+            Expr::Neg(inner) => {
+                let value = inner.eval(lookup)?;
+                let n = value.as_number().ok_or("取负的值不是数字")?;
+                Ok(Value::Number(-n))
+            }
+            Expr::Binary(op, left, right) => eval_binary(*op, left, right, lookup),
+            Expr::Call(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| arg.eval(lookup))
+                    .collect::<Result<Vec<_>, _>>()?;
+                call_function(name, &args)
+            }
+        }
+    }
+}
+
+fn eval_binary(
+    op: BinaryOp,
+    left: &Expr,
+    right: &Expr,
+    lookup: &dyn Fn(&str) -> Value,
+) -> Result<Value, String> {
+    // 逻辑运算短路求值
+    match op {
+        BinaryOp::Or => {
+            return Ok(Value::Bool(
+                left.eval(lookup)?.is_truthy() || right.eval(lookup)?.is_truthy(),
+            ))
+        }
+        BinaryOp::And => {
+            return Ok(Value::Bool(
+                left.eval(lookup)?.is_truthy() && right.eval(lookup)?.is_truthy(),
+            ))
+        }
+        _ => {}
+    }
+
+    let left = left.eval(lookup)?;
+    let right = right.eval(lookup)?;
+    let numbers = left.as_number().zip(right.as_number());
+
+    match op {
+        BinaryOp::Eq | BinaryOp::Ne => {
+            let equal = match numbers {
+                Some((a, b)) => a == b,
+                None => left.to_string() == right.to_string(),
+            };
+            Ok(Value::Bool(equal == (op == BinaryOp::Eq)))
+        }
+        BinaryOp::Lt | BinaryOp::Le | BinaryOp::Gt | BinaryOp::Ge => {
+            // 未填写的值无法比较大小，条件视为不成立
+            let Some((a, b)) = numbers else {
+                return Ok(Value::Bool(false));
+            };
+            Ok(Value::Bool(match op {
+                BinaryOp::Lt => a < b,
+                BinaryOp::Le => a <= b,
+                BinaryOp::Gt => a > b,
+                _ => a >= b,
+            }))
+        }
+        BinaryOp::Add if numbers.is_none() => Ok(Value::Text(format!("{}{}", left, right))),
+        _ => {
+            let (a, b) = numbers.ok_or("参与运算的值不是数字")?;
+            match op {
+                BinaryOp::Add => Ok(Value::Number(a + b)),
+                BinaryOp::Sub => Ok(Value::Number(a - b)),
+                BinaryOp::Mul => Ok(Value::Number(a * b)),
+                _ if b == 0.0 => Err("除数不能为 0".to_string()),
+                _ => Ok(Value::Number(a / b)),
+            }
+        }
+    }
+}
+
+/// 内置函数参数的用途
+fn function_arg_usage(name: &str) -> VariableUsage {
+    match name {
+        "年龄" | "age" | "天数" | "days" => VariableUsage::Date,
+        "BMI" | "bmi" | "四舍五入" | "round" => VariableUsage::Number,
+        _ => VariableUsage::Text,
+    }
+}
+
+/// 调用内置函数
+///
+/// - `年龄(出生日期[, 参考日期])`：周岁
+/// - `天数(日期[, 参考日期])`：从该日期到参考日期（默认今天）的天数
+/// - `BMI(身高cm, 体重kg)`：体重指数，保留一位小数
+/// - `四舍五入(数值[, 小数位数])`
+fn call_function(name: &str, args: &[Value]) -> Result<Value, String> {
+    let date_arg = |index: usize| -> Result<NaiveDate, String> {
+        match args.get(index) {
+            Some(value) => value
+                .as_date()
+                .ok_or_else(|| format!("{} 的第 {} 个参数不是日期", name, index + 1)),
+            None => Ok(Local::now().date_naive()),
+        }
+    };
+    let number_arg = |index: usize| -> Result<f64, String> {
+        args.get(index)
+            .and_then(Value::as_number)
+            .ok_or_else(|| format!("{} 的第 {} 个参数不是数字", name, index + 1))
+    };
+
+    match name {
+        "年龄" | "age" => {
+            let birth = date_arg(0)?;
+            let reference = date_arg(1)?;
+            let mut age = reference.year() - birth.year();
+            if (reference.month(), reference.day()) < (birth.month(), birth.day()) {
+                age -= 1;
+            }
+            Ok(Value::Number(age as f64))
+        }
+        "天数" | "days" => {
+            let since = date_arg(0)?;
+            let reference = date_arg(1)?;
+            Ok(Value::Number((reference - since).num_days() as f64))
+        }
+        "BMI" | "bmi" => {
+            let height = number_arg(0)? / 100.0;
+            let weight = number_arg(1)?;
+            if height <= 0.0 {
+                return Err("身高必须大于 0".to_string());
+            }
+            Ok(Value::Number(round(weight / (height * height), 1)))
+        }
+        "四舍五入" | "round" => {
+            let digits = if args.len() > 1 { number_arg(1)? } else { 0.0 };
+            Ok(Value::Number(round(number_arg(0)?, digits as i32)))
+        }
+        _ => Err(format!("未知函数：{}", name)),
+    }
+}
+
+fn round(value: f64, digits: i32) -> f64 {
+    let factor = 10f64.powi(digits);
+    (value * factor).round() / factor
+}
+
+/// 词法单元
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Number(f64),
+    Text(String),
+    Ident(String),
+    Op(&'static str),
+    LParen,
+    RParen,
+    Comma,
+}
+
+const OPERATORS: &[&str] = &[
+    "==", "!=", ">=", "<=", "&&", "||", ">", "<", "+", "-", "*", "/", "!",
+];
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = source.trim_start();
+
+    while let Some(c) = rest.chars().next() {
+        if let Some(op) = OPERATORS.iter().find(|op| rest.starts_with(**op)) {
+            tokens.push(Token::Op(op));
+            rest = &rest[op.len()..];
+        } else if c == '(' || c == '（' {
+            tokens.push(Token::LParen);
+            rest = &rest[c.len_utf8()..];
+        } else if c == ')' || c == '）' {
+            tokens.push(Token::RParen);
+            rest = &rest[c.len_utf8()..];
+        } else if c == ',' || c == '，' {
+            tokens.push(Token::Comma);
+            rest = &rest[c.len_utf8()..];
+        } else if c == '"' || c == '“' {
+            let close = if c == '"' { '"' } else { '”' };
+            let body = &rest[c.len_utf8()..];
+            let end = body
+                .find(close)
+                .ok_or_else(|| format!("字符串未闭合：{}", rest))?;
+            tokens.push(Token::Text(body[..end].to_string()));
+            rest = &body[end + close.len_utf8()..];
+        } else if c.is_ascii_digit() {
+            let end = rest
+                .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+                .unwrap_or(rest.len());
+            let number = rest[..end]
+                .parse()
+                .map_err(|_| format!("无效的数字：{}", &rest[..end]))?;
+            tokens.push(Token::Number(number));
+            rest = &rest[end..];
+        } else {
+            let end = rest
+                .find(|c: char| c.is_whitespace() || "()（）,，\"“=!<>&|+-*/".contains(c))
+                .unwrap_or(rest.len());
+            if end == 0 {
+                return Err(format!("无法识别的字符：{}", c));
+            }
+            tokens.push(Token::Ident(rest[..end].to_string()));
+            rest = &rest[end..];
+        }
+        rest = rest.trim_start();
+    }
+
+    Ok(tokens)
+}
+
+/// 表达式允许的最大嵌套层数，避免过深的括号、取反或过长的运算链耗尽栈空间
+const MAX_DEPTH: usize = 64;
+
+/// 递归下降解析器
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+    /// 当前的嵌套层数
+    depth: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.pos).cloned();
+        self.pos += 1;
+        token
+    }
+
+    fn eat_op(&mut self, ops: &[&'static str]) -> Option<&'static str> {
+        match self.peek() {
+            Some(Token::Op(op)) if ops.contains(op) => {
+                let op = *op;
+                self.pos += 1;
+                Some(op)
+            }
+            _ => None,
+        }
+    }
+
+    /// 进入一层嵌套，超过 [`MAX_DEPTH`] 时返回错误
+    fn descend(&mut self) -> Result<(), String> {
+        if self.depth >= MAX_DEPTH {
+            return Err(format!("表达式嵌套超过 {} 层", MAX_DEPTH));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// 在下一层嵌套中解析
+    fn nested(&mut self, parse: fn(&mut Self) -> Result<Expr, String>) -> Result<Expr, String> {
+        self.descend()?;
+        let result = parse(self);
+        self.depth -= 1;
+        result
+    }
+
+    fn binary(
+        &mut self,
+        ops: &[&'static str],
+        next: fn(&mut Self) -> Result<Expr, String>,
+    ) -> Result<Expr, String> {
+        // 运算链生成左深的语法树，每多一个运算符计算时就多一层递归
+        let depth = self.depth;
+        let mut left = next(self)?;
+        let result = loop {
+            let Some(op) = self.eat_op(ops) else {
+                break Ok(left);
+            };
+            if let Err(e) = self.descend() {
+                break Err(e);
+            }
+            match next(self) {
+                Ok(right) => left = Expr::Binary(binary_op(op), Box::new(left), Box::new(right)),
+                Err(e) => break Err(e),
+            }
+        };
+        self.depth = depth;
+        result
+    }
+
+    fn or(&mut self) -> Result<Expr, String> {
+        self.binary(&["||"], Self::and)
+    }
+
+    fn and(&mut self) -> Result<Expr, String> {
+        self.binary(&["&&"], Self::comparison)
+    }
+
+    fn comparison(&mut self) -> Result<Expr, String> {
+        let left = self.additive()?;
+        match self.eat_op(&["==", "!=", ">=", "<=", ">", "<"]) {
+            Some(op) => {
+                let right = self.additive()?;
+                Ok(Expr::Binary(binary_op(op), Box::new(left), Box::new(right)))
+            }
+            None => Ok(left),
+        }
+    }
+
+    fn additive(&mut self) -> Result<Expr, String> {
+        self.binary(&["+", "-"], Self::multiplicative)
+    }
+
+    fn multiplicative(&mut self) -> Result<Expr, String> {
+        self.binary(&["*", "/"], Self::unary)
+    }
+
+    fn unary(&mut self) -> Result<Expr, String> {
+        match self.eat_op(&["!", "-"]) {
+            Some("!") => Ok(Expr::Not(Box::new(self.nested(Self::unary)?))),
+            Some(_) => Ok(Expr::Neg(Box::new(self.nested(Self::unary)?))),
+            None => self.primary(),
+        }
+    }
+
+    fn primary(&mut self) -> Result<Expr, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(Expr::Number(n)),
+            Some(Token::Text(text)) => Ok(Expr::Text(text)),
+            Some(Token::Ident(name)) => {
+                if self.peek() != Some(&Token::LParen) {
+                    return Ok(Expr::Variable(name));
+                }
+                self.pos += 1;
+                let mut args = Vec::new();
+                if self.peek() == Some(&Token::RParen) {
+                    self.pos += 1;
+                    return Ok(Expr::Call(name, args));
+                }
+                loop {
+                    args.push(self.nested(Self::or)?);
+                    match self.next() {
+                        Some(Token::Comma) => continue,
+                        Some(Token::RParen) => break,
+                        _ => return Err(format!("函数 {} 的参数列表未闭合", name)),
+                    }
+                }
+                Ok(Expr::Call(name, args))
+            }
+            Some(Token::LParen) => {
+                let expr = self.nested(Self::or)?;
+                match self.next() {
+                    Some(Token::RParen) => Ok(expr),
+                    _ => Err("括号未闭合".to_string()),
+                }
+            }
+            Some(token) => Err(format!("意外的符号：{:?}", token)),
+            None => Err("表达式不完整".to_string()),
+        }
+    }
+}
+
+fn binary_op(op: &str) -> BinaryOp {
+    match op {
+        "||" => BinaryOp::Or,
+        "&&" => BinaryOp::And,
+        "==" => BinaryOp::Eq,
+        "!=" => BinaryOp::Ne,
+        "<" => BinaryOp::Lt,
+        "<=" => BinaryOp::Le,
+        ">" => BinaryOp::Gt,
+        ">=" => BinaryOp::Ge,
+        "+" => BinaryOp::Add,
+        "-" => BinaryOp::Sub,
+        "*" => BinaryOp::Mul,
+        _ => BinaryOp::Div,
+    }
+}
+
+/// 解析表达式文本
+pub fn parse(source: &str) -> Result<Expr, String> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        pos: 0,
+        depth: 0,
+    };
+    let expr = parser.or()?;
+    match parser.peek() {
+        None => Ok(expr),
+        Some(token) => Err(format!("表达式中有多余的内容：{:?}", token)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str) -> Result<Value, String> {
+        let lookup = |name: &str| match name {
+            "体温" => Value::Text("38.5".to_string()),
+            "性别" => Value::Text("男".to_string()),
+            "出生日期" => Value::Text("1990-06-15".to_string()),
+            _ => Value::Null,
+        };
+        parse(source)?.eval(&lookup)
+    }
+
+    fn number(n: f64) -> Value {
+        Value::Number(n)
+    }
+
+    #[test]
+    fn arithmetic_follows_precedence() {
+        assert_eq!(eval("1 + 2 * 3"), Ok(number(7.0)));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(number(9.0)));
+        assert_eq!(eval("10 - 4 - 3"), Ok(number(3.0)));
+        assert_eq!(eval("-2 * -3"), Ok(number(6.0)));
+        assert_eq!(eval("（1 + 2）* 2"), Ok(number(6.0)));
+        assert!(eval("1 / 0").is_err());
+    }
+
+    #[test]
+    fn comparison_and_logic() {
+        assert_eq!(eval("体温 > 37.3"), Ok(Value::Bool(true)));
+        assert_eq!(eval("体温 >= 39 || 性别 == \"男\""), Ok(Value::Bool(true)));
+        assert_eq!(eval("性别 == “女” && 体温 > 37"), Ok(Value::Bool(false)));
+        assert_eq!(eval("!未填写"), Ok(Value::Bool(true)));
+        // 未填写的值无法比较大小
+        assert_eq!(eval("未填写 < 1"), Ok(Value::Bool(false)));
+        // 短路求值时不计算右侧出错的表达式
+        assert_eq!(eval("1 || 1 / 0"), Ok(Value::Bool(true)));
+    }
+
+    #[test]
+    fn text_concatenation() {
+        assert_eq!(eval("性别 + \"性\""), Ok(Value::Text("男性".to_string())));
+        assert_eq!(eval("体温 + 1"), Ok(number(39.5)));
+    }
+
+    #[test]
+    fn builtin_functions() {
+        assert_eq!(eval("年龄(出生日期, \"2024-06-14\")"), Ok(number(33.0)));
+        assert_eq!(eval("年龄(出生日期, \"2024年6月15日\")"), Ok(number(34.0)));
+        assert_eq!(
+            eval("天数(\"2024-03-01\", \"2024-03-05\")"),
+            Ok(number(4.0))
+        );
+        assert_eq!(eval("BMI(170, 65)"), Ok(number(22.5)));
+        assert_eq!(eval("四舍五入(2.71828, 2)"), Ok(number(2.72)));
+        assert!(eval("年龄(\"不是日期\")").is_err());
+        assert!(eval("BMI(0, 65)").is_err());
+        assert!(eval("未知(1)").is_err());
+    }
+
+    #[test]
+    fn number_display_trims_zeros() {
+        assert_eq!(number(3.0).to_string(), "3");
+        assert_eq!(number(2.50).to_string(), "2.5");
+        assert_eq!(number(1.0 / 3.0).to_string(), "0.33");
+        assert_eq!(number(-0.001).to_string(), "0");
+        assert_eq!(Value::Bool(true).to_string(), "是");
+    }
+
+    #[test]
+    fn syntax_errors() {
+        assert!(parse("(1 + 2").is_err());
+        assert!(parse("\"未闭合").is_err());
+        assert!(parse("1 +").is_err());
+        assert!(parse("1 2").is_err());
+        assert!(parse("年龄(1, 2").is_err());
+    }
+
+    #[test]
+    fn variables_record_usage() {
+        let expr = parse("年龄(出生日期) > 18 && 性别 == \"男\" || -体温").unwrap();
+        assert_eq!(
+            expr.variables(),
+            vec![
+                ("出生日期".to_string(), VariableUsage::Date),
+                ("性别".to_string(), VariableUsage::Text),
+                ("体温".to_string(), VariableUsage::Number),
+            ]
+        );
+    }
+
+    #[test]
+    fn nesting_is_limited() {
+        let nested = |depth: usize| format!("{}1{}", "(".repeat(depth), ")".repeat(depth));
+        assert!(parse(&nested(MAX_DEPTH - 1)).is_ok());
+        assert!(parse(&nested(MAX_DEPTH + 1)).is_err());
+        assert!(parse(&nested(100_000)).is_err());
+
+        assert!(parse(&"!".repeat(100_000)).is_err());
+
+        let chain = |terms: usize| vec!["1"; terms].join(" + ");
+        assert!(parse(&chain(MAX_DEPTH / 2)).is_ok());
+        assert!(parse(&chain(100_000)).is_err());
+    }
+}
@@ -0,0 +1,270 @@
+use serde::{Deserialize, Serialize};
+use tauri::{Monitor, PhysicalPosition, PhysicalSize};
+
+/// 悬浮窗口的窗口标签，capabilities 中按此标签授权
+pub const FLOAT_WINDOW_LABEL: &str = "float";
+
+/// 悬浮窗口的默认大小（逻辑像素）
+pub const DEFAULT_FLOAT_WIDTH: f64 = 274.0;
+pub const DEFAULT_FLOAT_HEIGHT: f64 = 350.0;
+
+/// 悬浮窗口允许的最低不透明度，避免窗口完全看不见
+pub const MIN_FLOAT_OPACITY: f64 = 0.2;
+
+/// 默认的呼出悬浮窗口快捷键
+pub const DEFAULT_FLOAT_HOTKEY: &str = "CommandOrControl+Shift+Space";
+
+/// 悬浮窗口与鼠标之间的距离（物理像素）
+const CURSOR_OFFSET: i32 = 12;
+
+// 在一个方向上定位：优先放在鼠标之后（右侧、下方），放不下时放在鼠标之前，
+// 都放不下时贴住可见区域的末端；窗口比可见区域还大时对齐起点
+fn place_on_axis(cursor: i32, length: i32, start: i32, end: i32) -> i32 {
+    let after = cursor + CURSOR_OFFSET;
+    let before = cursor - CURSOR_OFFSET - length;
+    let position = if after + length <= end {
+        after
+    } else if before >= start {
+        before
+    } else {
+        end - length
+    };
+    position.max(start)
+}
+
+/// 计算悬浮窗口的位置：显示在鼠标旁边，并限制在显示器的可见区域内
+///
+/// 可见区域为鼠标所在显示器去掉任务栏、程序坞后的工作区，坐标均为物理像素
+pub fn place_near_cursor(
+    cursor: PhysicalPosition<i32>,
+    window: PhysicalSize<u32>,
+    area_position: PhysicalPosition<i32>,
+    area_size: PhysicalSize<u32>,
+) -> PhysicalPosition<i32> {
+    PhysicalPosition::new(
+        place_on_axis(
+            cursor.x,
+            window.width as i32,
+            area_position.x,
+            area_position.x + area_size.width as i32,
+        ),
+        place_on_axis(
+            cursor.y,
+            window.height as i32,
+            area_position.y,
+            area_position.y + area_size.height as i32,
+        ),
+    )
+}
+
+/// 悬浮窗口的位置和大小（物理像素）
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct FloatGeometry {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+/// 悬浮窗口的状态，按显示器布局分别保存
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FloatWindowState {
+    /// 未保存过时为空，使用默认的位置和大小
+    pub geometry: Option<FloatGeometry>,
+    /// 置顶显示
+    pub pinned: bool,
+    /// 不透明度，范围为 [`MIN_FLOAT_OPACITY`] 到 1
+    pub opacity: f64,
+    /// 上次是否可见，启动时据此决定是否显示
+    pub visible: bool,
+}
+
+impl Default for FloatWindowState {
+    fn default() -> Self {
+        FloatWindowState {
+            geometry: None,
+            pinned: true,
+            opacity: 1.0,
+            visible: true,
+        }
+    }
+}
+
+/// 把不透明度限制在允许的范围内
+pub fn clamp_opacity(opacity: f64) -> f64 {
+    if opacity.is_nan() {
+        return 1.0;
+    }
+    opacity.clamp(MIN_FLOAT_OPACITY, 1.0)
+}
+
+/// 显示器布局的标识，由各显示器的位置、分辨率和缩放比例组成，与显示器的顺序无关
+pub fn layout_key(monitors: &[Monitor]) -> String {
+    let mut parts: Vec<String> = monitors
+        .iter()
+        .map(|monitor| {
+            let position = monitor.position();
+            let size = monitor.size();
+            format!(
+                "{},{},{}x{}@{}",
+                position.x,
+                position.y,
+                size.width,
+                size.height,
+                monitor.scale_factor()
+            )
+        })
+        .collect();
+    parts.sort();
+    parts.join(";")
+}
+
+/// 点是否在窗口范围内，坐标均为物理像素
+pub fn contains(
+    position: PhysicalPosition<i32>,
+    size: PhysicalSize<u32>,
+    point: PhysicalPosition<f64>,
+) -> bool {
+    let (left, top) = (f64::from(position.x), f64::from(position.y));
+    let (right, bottom) = (left + f64::from(size.width), top + f64::from(size.height));
+    point.x >= left && point.x < right && point.y >= top && point.y < bottom
+}
+
+/// 悬浮窗口中页面内容实际占据的区域，相对于窗口内容区的左上角（物理像素）
+///
+/// 收起时只有搜索图标，只有鼠标在该区域内时窗口才接收鼠标事件
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct HitRect {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl HitRect {
+    /// 换算为屏幕坐标，`origin` 为窗口内容区左上角的屏幕坐标
+    pub fn on_screen(
+        &self,
+        origin: PhysicalPosition<i32>,
+    ) -> (PhysicalPosition<i32>, PhysicalSize<u32>) {
+        (
+            PhysicalPosition::new(origin.x + self.x, origin.y + self.y),
+            PhysicalSize::new(self.width, self.height),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const AREA_POSITION: PhysicalPosition<i32> = PhysicalPosition { x: 0, y: 0 };
+    const AREA_SIZE: PhysicalSize<u32> = PhysicalSize {
+        width: 1920,
+        height: 1040,
+    };
+    const WINDOW: PhysicalSize<u32> = PhysicalSize {
+        width: 274,
+        height: 350,
+    };
+
+    fn place(x: i32, y: i32) -> PhysicalPosition<i32> {
+        place_near_cursor(
+            PhysicalPosition::new(x, y),
+            WINDOW,
+            AREA_POSITION,
+            AREA_SIZE,
+        )
+    }
+
+    #[test]
+    fn places_after_cursor_when_it_fits() {
+        assert_eq!(place(100, 100), PhysicalPosition::new(112, 112));
+    }
+
+    #[test]
+    fn flips_before_cursor_near_the_edges() {
+        assert_eq!(place(1900, 1000), PhysicalPosition::new(1614, 638));
+    }
+
+    #[test]
+    fn clamps_to_the_work_area() {
+        // 两侧都放不下时贴住可见区域的末端
+        let narrow = PhysicalSize::new(300, 400);
+        assert_eq!(
+            place_near_cursor(
+                PhysicalPosition::new(150, 200),
+                WINDOW,
+                AREA_POSITION,
+                narrow
+            ),
+            PhysicalPosition::new(26, 50)
+        );
+        // 窗口比可见区域还大时对齐起点
+        let tiny = PhysicalSize::new(200, 200);
+        assert_eq!(
+            place_near_cursor(PhysicalPosition::new(100, 100), WINDOW, AREA_POSITION, tiny),
+            AREA_POSITION
+        );
+    }
+
+    #[test]
+    fn respects_offset_monitors() {
+        // 主显示器左侧的副显示器，坐标为负数
+        let position = PhysicalPosition::new(-1280, 0);
+        let size = PhysicalSize::new(1280, 1024);
+        assert_eq!(
+            place_near_cursor(PhysicalPosition::new(-10, 10), WINDOW, position, size),
+            PhysicalPosition::new(-296, 22)
+        );
+    }
+
+    #[test]
+    fn hit_rect_is_relative_to_the_window() {
+        let rect = HitRect {
+            x: 200,
+            y: 2,
+            width: 72,
+            height: 50,
+        };
+        let (position, size) = rect.on_screen(PhysicalPosition::new(-500, 100));
+        assert_eq!(position, PhysicalPosition::new(-300, 102));
+        assert!(contains(
+            position,
+            size,
+            PhysicalPosition::new(-250.0, 120.0)
+        ));
+        // 窗口内但不在内容区域内
+        assert!(!contains(
+            position,
+            size,
+            PhysicalPosition::new(-450.0, 300.0)
+        ));
+    }
+
+    #[test]
+    fn opacity_is_clamped() {
+        assert_eq!(clamp_opacity(0.0), MIN_FLOAT_OPACITY);
+        assert_eq!(clamp_opacity(2.0), 1.0);
+        assert_eq!(clamp_opacity(0.5), 0.5);
+        assert_eq!(clamp_opacity(f64::NAN), 1.0);
+    }
+
+    #[test]
+    fn contains_is_half_open() {
+        let position = PhysicalPosition::new(10, 20);
+        let size = PhysicalSize::new(100, 50);
+        assert!(contains(position, size, PhysicalPosition::new(10.0, 20.0)));
+        assert!(contains(position, size, PhysicalPosition::new(109.5, 69.5)));
+        assert!(!contains(
+            position,
+            size,
+            PhysicalPosition::new(110.0, 20.0)
+        ));
+        assert!(!contains(position, size, PhysicalPosition::new(10.0, 70.0)));
+        assert!(!contains(position, size, PhysicalPosition::new(9.9, 30.0)));
+    }
+}
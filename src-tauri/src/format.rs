@@ -0,0 +1,135 @@
+use crate::database::TemplateSection;
+use serde::{Deserialize, Serialize};
+
+/// 默认格式方案的ID，该方案可以修改但不能删除
+pub const DEFAULT_PROFILE_ID: &str = "default";
+
+/// 只输出内容的内置格式方案的ID，用于单独粘贴一个章节；该方案不在方案列表中，也不能修改
+pub const CONTENT_ONLY_PROFILE_ID: &str = "contentOnly";
+
+/// 内容缩进允许的最大空格数
+pub const MAX_INDENT: usize = 16;
+
+/// 章节标题的排版方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum TitleLayout {
+    /// 标题与内容在同一行：`标题：内容`
+    Inline,
+    /// 标题单独一行，内容另起一行
+    OwnLine,
+    /// 不输出标题
+    Hidden,
+}
+
+/// 章节编号方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum Numbering {
+    None,
+    /// `1. 标题`
+    Arabic,
+    /// `一、标题`
+    Chinese,
+}
+
+/// 文本输出的格式方案
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormatProfile {
+    /// 新建时为空，由后端分配；缺省时也视为新建，不会覆盖默认方案
+    #[serde(default)]
+    pub id: String,
+    pub name: String,
+    pub title_layout: TitleLayout,
+    pub numbering: Numbering,
+    /// 标题与内容间使用全角冒号，否则使用半角冒号
+    pub full_width_colon: bool,
+    /// 内容每行前缩进的空格数，最多 [`MAX_INDENT`] 个
+    pub indent: usize,
+    /// 章节之间插入空行
+    pub blank_line_between_sections: bool,
+    /// 跳过内容为空的章节
+    pub skip_empty_sections: bool,
+}
+
+impl Default for FormatProfile {
+    /// 与前端复制一致的格式：`标题：内容`，章节间换行
+    fn default() -> Self {
+        FormatProfile {
+            id: DEFAULT_PROFILE_ID.to_string(),
+            name: "默认".to_string(),
+            title_layout: TitleLayout::Inline,
+            numbering: Numbering::None,
+            full_width_colon: true,
+            indent: 0,
+            blank_line_between_sections: false,
+            skip_empty_sections: false,
+        }
+    }
+}
+
+impl FormatProfile {
+    /// 不输出标题和编号，只输出章节内容
+    pub fn content_only() -> Self {
+        FormatProfile {
+            id: CONTENT_ONLY_PROFILE_ID.to_string(),
+            name: "仅内容".to_string(),
+            title_layout: TitleLayout::Hidden,
+            ..FormatProfile::default()
+        }
+    }
+}
+
+/// 将数字转换为中文序号，支持 1 到 99
+fn chinese_number(number: usize) -> String {
+    const DIGITS: [&str; 10] = ["零", "一", "二", "三", "四", "五", "六", "七", "八", "九"];
+    let (tens, units) = (number / 10, number % 10);
+    let units = if units == 0 { "" } else { DIGITS[units] };
+    match tens {
+        0 => DIGITS[number].to_string(),
+        1 => format!("十{}", units),
+        2..=9 => format!("{}十{}", DIGITS[tens], units),
+        _ => number.to_string(),
+    }
+}
+
+/// 按格式方案拼接章节，生成用于粘贴的文本
+pub fn format_sections(sections: &[TemplateSection], profile: &FormatProfile) -> String {
+    let colon = if profile.full_width_colon {
+        "："
+    } else {
+        ": "
+    };
+    let indent = " ".repeat(profile.indent.min(MAX_INDENT));
+    let separator = if profile.blank_line_between_sections {
+        "\n\n"
+    } else {
+        "\n"
+    };
+
+    sections
+        .iter()
+        .filter(|section| !profile.skip_empty_sections || !section.content.trim().is_empty())
+        .enumerate()
+        .map(|(index, section)| {
+            let title = match profile.numbering {
+                Numbering::None => section.title.clone(),
+                Numbering::Arabic => format!("{}. {}", index + 1, section.title),
+                Numbering::Chinese => format!("{}、{}", chinese_number(index + 1), section.title),
+            };
+            // 标题与内容同行时，内容首行紧跟在标题后，不缩进
+            let mut content = section.content.replace('\n', &format!("\n{}", indent));
+            if profile.title_layout != TitleLayout::Inline {
+                content.insert_str(0, &indent);
+            }
+
+            match profile.title_layout {
+                TitleLayout::Inline => format!("{}{}{}", title, colon, content),
+                TitleLayout::OwnLine => format!("{}\n{}", title, content),
+                TitleLayout::Hidden => content,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(separator)
+}
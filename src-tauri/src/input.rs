@@ -0,0 +1,104 @@
+use crate::platform::history::{self, WindowInfo};
+
+use serde::{Deserialize, Serialize};
+
+/// 把文本送入目标窗口的方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PasteStrategy {
+    /// 写入剪贴板后发送 Shift+Insert
+    #[default]
+    ShiftInsert,
+    /// 写入剪贴板后发送 Ctrl+V
+    CtrlV,
+    /// 不经过剪贴板，逐字模拟键盘输入，`delay` 为每个字符之间的间隔（毫秒）
+    Type {
+        #[serde(default)]
+        delay: u64,
+    },
+}
+
+/// 切换到目标窗口后、粘贴前的默认等待时间（毫秒）
+pub const DEFAULT_FOCUS_DELAY: u64 = 100;
+
+/// 粘贴后等待多久再恢复用户原来的剪贴板内容（毫秒），过早恢复会导致目标窗口粘贴到旧内容
+pub const DEFAULT_RESTORE_DELAY: u64 = 300;
+
+/// 粘贴时换行符的处理方式
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum NewlineMode {
+    /// 使用 `\n`
+    #[default]
+    Lf,
+    /// 使用 `\r\n`，部分 Windows 程序需要
+    CrLf,
+    /// 替换为空格，用于单行输入框
+    Space,
+}
+
+impl NewlineMode {
+    /// 按设置转换文本中的换行符
+    pub fn apply(self, text: &str) -> String {
+        let text = text.replace("\r\n", "\n");
+        match self {
+            NewlineMode::Lf => text,
+            NewlineMode::CrLf => text.replace('\n', "\r\n"),
+            NewlineMode::Space => text.replace('\n', " "),
+        }
+    }
+}
+
+/// 针对某类目标窗口的粘贴方案，按目标窗口的进程名或标题匹配
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppPasteProfile {
+    pub id: String,
+    pub name: String,
+    /// 进程名模式，支持 `*` 和 `?` 通配符，不区分大小写，为空时不按进程名匹配
+    pub process_pattern: String,
+    /// 窗口标题模式，规则同上，为空时不按标题匹配
+    pub title_pattern: String,
+    pub strategy: PasteStrategy,
+    /// 切换到目标窗口后等待多久再粘贴（毫秒）
+    pub focus_delay: u64,
+    /// 粘贴后等待多久再恢复剪贴板（毫秒）
+    pub restore_delay: u64,
+    /// 使用的格式方案ID，为空时使用默认方案
+    pub format_profile_id: Option<String>,
+    pub newline: NewlineMode,
+}
+
+impl Default for AppPasteProfile {
+    fn default() -> Self {
+        AppPasteProfile {
+            id: String::new(),
+            name: String::new(),
+            process_pattern: String::new(),
+            title_pattern: String::new(),
+            strategy: PasteStrategy::default(),
+            focus_delay: DEFAULT_FOCUS_DELAY,
+            restore_delay: DEFAULT_RESTORE_DELAY,
+            format_profile_id: None,
+            newline: NewlineMode::default(),
+        }
+    }
+}
+
+impl AppPasteProfile {
+    /// 进程名或标题任一模式匹配即可
+    pub fn matches(&self, window: &WindowInfo) -> bool {
+        (!self.process_pattern.is_empty()
+            && history::matches_pattern(&self.process_pattern, &window.process_name))
+            || (!self.title_pattern.is_empty()
+                && history::matches_pattern(&self.title_pattern, &window.title))
+    }
+}
+
+/// 按顺序查找第一个匹配目标窗口的粘贴方案
+pub fn find_app_profile<'a>(
+    profiles: &'a [AppPasteProfile],
+    window: &WindowInfo,
+) -> Option<&'a AppPasteProfile> {
+    profiles.iter().find(|profile| profile.matches(window))
+}
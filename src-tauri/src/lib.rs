@@ -1,9 +1,19 @@
 mod commands;
+mod composition;
 mod database;
+mod expression;
+mod float;
+mod format;
+mod input;
+mod platform;
+mod render;
+mod suggestion;
+mod validation;
 
 use commands::*;
 use database::DatabaseManager;
 use std::sync::Mutex;
+use tauri_plugin_global_shortcut::ShortcutState;
 
 pub static MAIN_WINDOW_TITLE: &str = "悬浮搜索";
 /// 应用状态，包含数据库管理器
@@ -15,11 +25,25 @@ pub struct AppState {
 pub fn run() {
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
+        // 快捷键只在 Rust 端注册（见 commands::float::register_float_hotkey），
+        // 前端不直接调用该插件，因此 capabilities 中没有授予 global-shortcut 权限
+        .plugin(
+            tauri_plugin_global_shortcut::Builder::new()
+                .with_handler(|app, _shortcut, event| {
+                    if event.state() == ShortcutState::Pressed {
+                        if let Err(e) = summon_float_window(app) {
+                            log::error!("呼出悬浮窗口失败: {}", e);
+                        }
+                    }
+                })
+                .build(),
+        )
         .manage(AppState {
             db: Mutex::new(None),
         })
-        .setup(|_app| {
+        .setup(|app| {
             observe_app();
+            track_float_hover(app.handle().clone());
 
             Ok(())
         })
@@ -30,17 +54,65 @@ pub fn run() {
             save_template,
             delete_template,
             toggle_template_favorite,
+            bulk_delete_templates,
+            bulk_set_template_favorite,
+            bulk_set_template_disease,
+            bulk_set_template_type,
+            bulk_add_template_tags,
+            bulk_remove_template_tags,
             search_templates,
             get_all_diseases,
             get_all_template_types,
             get_all_tags,
             save_tag,
             import_templates,
+            get_validation_rules,
+            save_validation_rules,
+            get_format_profiles,
+            save_format_profile,
+            delete_format_profile,
+            get_paste_strategy,
+            save_paste_strategy,
+            get_window_exclusion_rules,
+            save_window_exclusion_rules,
+            get_app_paste_profiles,
+            save_app_paste_profile,
+            delete_app_paste_profile,
+            get_suggestion_rules,
+            save_suggestion_rules,
+            suggest_templates,
+            get_float_hotkey,
+            save_float_hotkey,
+            show_float_window,
+            hide_float_window,
+            toggle_float_window,
+            get_float_window_state,
+            set_float_window_pinned,
+            set_float_window_opacity,
+            set_float_hit_rect,
+            duplicate_template,
+            split_template,
+            merge_templates,
+            get_section_dependents,
             reset_tags,
             clear_templates,
+            get_all_snippets,
+            get_snippet_by_id,
+            save_snippet,
+            delete_snippet,
+            record_snippet_usage,
+            search_snippets,
+            get_all_snippet_categories,
+            get_snippet_dependents,
+            get_template_variables,
+            get_template_form,
+            render_template,
             create_float_window,
-            get_mouse_position,
-            paste
+            paste,
+            get_window_history,
+            get_paste_targets,
+            paste_text,
+            paste_fields
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -0,0 +1,218 @@
+use super::WindowId;
+use crate::MAIN_WINDOW_TITLE;
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+/// 最多保留的前台窗口记录数
+const HISTORY_LIMIT: usize = 20;
+
+static HISTORY: Mutex<VecDeque<WindowInfo>> = Mutex::new(VecDeque::new());
+static RULES: Mutex<Option<WindowExclusionRules>> = Mutex::new(None);
+static LAST_TARGET: Mutex<Option<WindowId>> = Mutex::new(None);
+
+/// 顶层窗口
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct WindowInfo {
+    pub id: WindowId,
+    pub title: String,
+    pub process_id: u32,
+    /// 进程的可执行文件名，例如 `chrome.exe`，无法获取时为空
+    pub process_name: String,
+    /// 图标标识，同一程序的窗口相同，可用于缓存图标
+    /// （Windows 上为可执行文件路径，X11 上为 WM_CLASS）
+    pub icon_id: String,
+    /// 最近一次切换到前台的时间（毫秒时间戳），未记录过时为 0
+    pub activated_at: i64,
+}
+
+/// 不记录为粘贴目标的窗口，本程序自己的窗口总是被排除
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct WindowExclusionRules {
+    /// 进程名模式，支持 `*` 和 `?` 通配符，不区分大小写
+    pub processes: Vec<String>,
+    /// 窗口标题模式，规则同上
+    pub titles: Vec<String>,
+    /// 排除没有标题的窗口，如桌面、任务栏
+    pub exclude_untitled: bool,
+}
+
+impl Default for WindowExclusionRules {
+    fn default() -> Self {
+        WindowExclusionRules {
+            processes: Vec::new(),
+            titles: vec![MAIN_WINDOW_TITLE.to_string()],
+            exclude_untitled: true,
+        }
+    }
+}
+
+impl WindowExclusionRules {
+    fn excludes(&self, window: &WindowInfo) -> bool {
+        window.process_id == std::process::id()
+            || (self.exclude_untitled && window.title.trim().is_empty())
+            || self
+                .processes
+                .iter()
+                .any(|pattern| matches_pattern(pattern, &window.process_name))
+            || self
+                .titles
+                .iter()
+                .any(|pattern| matches_pattern(pattern, &window.title))
+    }
+}
+
+/// 通配符匹配，`*` 匹配任意个字符，`?` 匹配一个字符，不区分大小写
+pub fn matches_pattern(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    // 回溯到最近一个 `*` 重新匹配
+    let (mut p, mut t) = (0, 0);
+    let mut star: Option<(usize, usize)> = None;
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            star = Some((p, t));
+            p += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+fn with_rules<T>(f: impl FnOnce(&WindowExclusionRules) -> T) -> T {
+    match RULES.lock() {
+        Ok(rules) => f(rules.as_ref().unwrap_or(&WindowExclusionRules::default())),
+        Err(_) => f(&WindowExclusionRules::default()),
+    }
+}
+
+/// 窗口是否被排除，被排除的窗口不作为粘贴目标
+pub fn is_excluded(window: &WindowInfo) -> bool {
+    with_rules(|rules| rules.excludes(window))
+}
+
+/// 更新排除规则，并移除历史中已被排除的窗口
+pub fn set_rules(rules: WindowExclusionRules) {
+    if let Ok(mut history) = HISTORY.lock() {
+        history.retain(|window| !rules.excludes(window));
+    }
+    if let Ok(mut current) = RULES.lock() {
+        *current = Some(rules);
+    }
+}
+
+/// 记录切换到前台的窗口，被排除的窗口不记录
+pub fn record(window: WindowInfo) {
+    if is_excluded(&window) {
+        return;
+    }
+    if let Ok(mut history) = HISTORY.lock() {
+        history.retain(|existing| existing.id != window.id);
+        history.push_front(window);
+        history.truncate(HISTORY_LIMIT);
+    }
+}
+
+/// 最近切换到前台的窗口，从新到旧排列
+pub fn recent_windows() -> Vec<WindowInfo> {
+    HISTORY
+        .lock()
+        .map(|history| history.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// 窗口最近一次切换到前台的时间，未记录过时返回 0
+pub fn activated_at(id: WindowId) -> i64 {
+    HISTORY
+        .lock()
+        .ok()
+        .and_then(|history| {
+            history
+                .iter()
+                .find(|window| window.id == id)
+                .map(|window| window.activated_at)
+        })
+        .unwrap_or_default()
+}
+
+/// 记住本次运行中最后一次手动选择的粘贴目标
+pub fn set_last_target(id: WindowId) {
+    if let Ok(mut last_target) = LAST_TARGET.lock() {
+        *last_target = Some(id);
+    }
+}
+
+/// 本次运行中最后一次手动选择的粘贴目标
+pub fn last_target() -> Option<WindowId> {
+    LAST_TARGET.lock().ok().and_then(|last_target| *last_target)
+}
+
+/// 最近一个切换到前台的窗口，即粘贴的目标
+pub fn latest() -> Option<WindowInfo> {
+    HISTORY
+        .lock()
+        .ok()
+        .and_then(|history| history.front().cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(title: &str, process_name: &str) -> WindowInfo {
+        WindowInfo {
+            id: 1,
+            title: title.to_string(),
+            process_id: 0,
+            process_name: process_name.to_string(),
+            icon_id: String::new(),
+            activated_at: 0,
+        }
+    }
+
+    #[test]
+    fn pattern_wildcards() {
+        assert!(matches_pattern("chrome.exe", "chrome.exe"));
+        assert!(matches_pattern("CHROME.EXE", "chrome.exe"));
+        assert!(matches_pattern("*.exe", "chrome.exe"));
+        assert!(matches_pattern("chr?me*", "chrome.exe"));
+        assert!(matches_pattern("*", ""));
+        assert!(matches_pattern("*电子病历*", "某医院电子病历系统"));
+        assert!(matches_pattern("a*b*c", "aXbYbZc"));
+        assert!(!matches_pattern("?", ""));
+        assert!(!matches_pattern("*.exe", "chrome.exe.bak"));
+        assert!(!matches_pattern("a*b*c", "aXbYbZ"));
+        assert!(!matches_pattern("", "chrome"));
+    }
+
+    #[test]
+    fn exclusion_rules() {
+        let rules = WindowExclusionRules {
+            processes: vec!["explorer*".to_string()],
+            titles: vec!["*设置".to_string()],
+            exclude_untitled: true,
+        };
+        assert!(rules.excludes(&window("", "notepad.exe")));
+        assert!(rules.excludes(&window("文件", "Explorer.EXE")));
+        assert!(rules.excludes(&window("系统设置", "control.exe")));
+        assert!(!rules.excludes(&window("病程记录", "emr.exe")));
+
+        let own = WindowInfo {
+            process_id: std::process::id(),
+            ..window("病程记录", "emr.exe")
+        };
+        assert!(rules.excludes(&own));
+    }
+}
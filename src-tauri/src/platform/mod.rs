@@ -0,0 +1,136 @@
+use serde::Serialize;
+use std::fmt;
+
+pub mod history;
+#[cfg(not(any(windows, target_os = "linux")))]
+mod unsupported;
+#[cfg(windows)]
+mod windows;
+#[cfg(target_os = "linux")]
+mod x11;
+
+use history::WindowInfo;
+
+/// 窗口标识：Windows 上为 HWND，X11 上为窗口ID
+pub type WindowId = u64;
+
+/// 粘贴失败的原因
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum PasteError {
+    /// 还没有记录到可以粘贴的目标窗口
+    NoPreviousWindow,
+    /// 无法把目标窗口切换到前台
+    FocusFailed { message: String },
+    /// 输入模拟或窗口系统不可用，例如无法连接 X 服务器、当前平台不支持
+    BackendUnavailable { message: String },
+    /// 发送按键失败
+    KeySendFailed { message: String },
+    /// 读写剪贴板失败
+    ClipboardFailed { message: String },
+    /// 其他错误，如模板不存在、数据库错误
+    Failed { message: String },
+}
+
+impl fmt::Display for PasteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PasteError::NoPreviousWindow => write!(f, "No previous window to paste into"),
+            PasteError::FocusFailed { message } => write!(f, "Failed to focus window: {}", message),
+            PasteError::BackendUnavailable { message } => {
+                write!(f, "Input backend unavailable: {}", message)
+            }
+            PasteError::KeySendFailed { message } => write!(f, "Failed to send keys: {}", message),
+            PasteError::ClipboardFailed { message } => write!(f, "Clipboard error: {}", message),
+            PasteError::Failed { message } => write!(f, "{}", message),
+        }
+    }
+}
+
+impl From<String> for PasteError {
+    fn from(message: String) -> Self {
+        PasteError::Failed { message }
+    }
+}
+
+impl From<&str> for PasteError {
+    fn from(message: &str) -> Self {
+        PasteError::Failed {
+            message: message.to_string(),
+        }
+    }
+}
+
+/// 粘贴剪贴板内容的快捷键
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PasteKeys {
+    ShiftInsert,
+    CtrlV,
+}
+
+/// 剪贴板中的文本和 HTML 内容
+///
+/// `html` 为平台原生格式的原始数据（Windows 上为 CF_HTML，X11 上为 text/html），只用于原样恢复。
+#[derive(Debug, Clone, Default)]
+pub struct ClipboardContents {
+    pub text: Option<String>,
+    pub html: Option<Vec<u8>>,
+}
+
+impl ClipboardContents {
+    pub fn text(text: &str) -> Self {
+        ClipboardContents {
+            text: Some(text.to_string()),
+            html: None,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.text.is_none() && self.html.is_none()
+    }
+}
+
+/// 粘贴所需的平台相关操作
+///
+/// Windows 使用 WinAPI 实现，Linux 使用 X11（EWMH 和 XTest）实现，其他平台的实现只返回错误。
+pub trait Platform: Send + Sync {
+    /// 开始监听前台窗口的切换，切换时调用 [`history::record`] 记录窗口
+    fn observe_foreground(&self) -> Result<(), PasteError>;
+
+    /// 列出可以作为粘贴目标的顶层窗口，按前后顺序排列，不含被排除的窗口
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, PasteError>;
+
+    /// 读取当前的前台窗口，没有前台窗口时返回 `None`
+    fn foreground_window(&self) -> Result<Option<WindowInfo>, PasteError>;
+
+    /// 读取窗口的标题和进程信息
+    fn window_info(&self, window: WindowId) -> Result<WindowInfo, PasteError>;
+
+    /// 把窗口切换到前台
+    fn focus_window(&self, window: WindowId) -> Result<(), PasteError>;
+
+    /// 读取剪贴板中的文本和 HTML 内容
+    fn read_clipboard(&self) -> Result<ClipboardContents, PasteError>;
+
+    /// 清空剪贴板并写入内容
+    fn write_clipboard(&self, contents: &ClipboardContents) -> Result<(), PasteError>;
+
+    /// 向当前焦点窗口发送粘贴快捷键
+    fn send_paste_keys(&self, keys: PasteKeys) -> Result<(), PasteError>;
+}
+
+/// 当前平台的实现
+pub fn current() -> &'static dyn Platform {
+    #[cfg(windows)]
+    {
+        &windows::WindowsPlatform
+    }
+    #[cfg(target_os = "linux")]
+    {
+        &x11::X11Platform
+    }
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        &unsupported::UnsupportedPlatform
+    }
+}
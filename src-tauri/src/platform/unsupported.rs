@@ -0,0 +1,45 @@
+use super::history::WindowInfo;
+use super::{ClipboardContents, PasteError, PasteKeys, Platform, WindowId};
+
+fn unsupported() -> PasteError {
+    PasteError::BackendUnavailable {
+        message: "Paste is not supported on this platform".to_string(),
+    }
+}
+
+/// 不支持的平台，所有操作都返回错误
+pub struct UnsupportedPlatform;
+
+impl Platform for UnsupportedPlatform {
+    fn observe_foreground(&self) -> Result<(), PasteError> {
+        Err(unsupported())
+    }
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, PasteError> {
+        Err(unsupported())
+    }
+
+    fn foreground_window(&self) -> Result<Option<WindowInfo>, PasteError> {
+        Err(unsupported())
+    }
+
+    fn window_info(&self, _window: WindowId) -> Result<WindowInfo, PasteError> {
+        Err(unsupported())
+    }
+
+    fn focus_window(&self, _window: WindowId) -> Result<(), PasteError> {
+        Err(unsupported())
+    }
+
+    fn read_clipboard(&self) -> Result<ClipboardContents, PasteError> {
+        Err(unsupported())
+    }
+
+    fn write_clipboard(&self, _contents: &ClipboardContents) -> Result<(), PasteError> {
+        Err(unsupported())
+    }
+
+    fn send_paste_keys(&self, _keys: PasteKeys) -> Result<(), PasteError> {
+        Err(unsupported())
+    }
+}
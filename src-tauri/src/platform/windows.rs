@@ -0,0 +1,345 @@
+use super::history::{self, WindowInfo};
+use super::{ClipboardContents, PasteError, PasteKeys, Platform, WindowId};
+
+use std::ffi::OsString;
+use std::os::windows::ffi::OsStringExt;
+use std::path::Path;
+use std::ptr;
+
+use enigo::{
+    Direction::{Click, Press, Release},
+    Enigo, Key, Keyboard, Settings,
+};
+use winapi::shared::minwindef::{BOOL, DWORD, FALSE, LPARAM, TRUE, UINT};
+use winapi::shared::windef::{HWINEVENTHOOK, HWND};
+use winapi::um::handleapi::CloseHandle;
+use winapi::um::processthreadsapi::OpenProcess;
+use winapi::um::winbase::{
+    GlobalAlloc, GlobalFree, GlobalLock, GlobalSize, GlobalUnlock, QueryFullProcessImageNameW,
+    GMEM_MOVEABLE,
+};
+use winapi::um::winnt::PROCESS_QUERY_LIMITED_INFORMATION;
+use winapi::um::winuser::{
+    CloseClipboard, EmptyClipboard, EnumWindows, GetClipboardData, GetForegroundWindow, GetWindow,
+    GetWindowLongW, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId, IsWindow,
+    IsWindowVisible, OpenClipboard, RegisterClipboardFormatW, SetClipboardData,
+    SetForegroundWindow, SetWinEventHook, CF_UNICODETEXT, EVENT_SYSTEM_FOREGROUND, GWL_EXSTYLE,
+    GW_OWNER, WINEVENT_OUTOFCONTEXT, WS_EX_TOOLWINDOW,
+};
+
+fn wait(millis: u64) {
+    use std::{thread, time};
+
+    thread::sleep(time::Duration::from_millis(millis));
+}
+
+// 获取窗口标题
+unsafe fn get_window_title(hwnd: HWND) -> String {
+    let length = GetWindowTextLengthW(hwnd);
+
+    if length == 0 {
+        return String::new();
+    }
+
+    let mut buffer: Vec<u16> = vec![0; (length + 1) as usize];
+
+    GetWindowTextW(hwnd, buffer.as_mut_ptr(), length + 1);
+
+    OsString::from_wide(&buffer[..length as usize])
+        .to_string_lossy()
+        .into_owned()
+}
+
+// 获取窗口所属的进程ID和可执行文件路径
+unsafe fn get_window_process(hwnd: HWND) -> (u32, String) {
+    let mut process_id: DWORD = 0;
+    GetWindowThreadProcessId(hwnd, &mut process_id);
+    if process_id == 0 {
+        return (0, String::new());
+    }
+
+    let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, FALSE, process_id);
+    if process.is_null() {
+        return (process_id, String::new());
+    }
+    let mut buffer: Vec<u16> = vec![0; 1024];
+    let mut length = buffer.len() as DWORD;
+    let succeeded = QueryFullProcessImageNameW(process, 0, buffer.as_mut_ptr(), &mut length);
+    CloseHandle(process);
+    if succeeded == 0 {
+        return (process_id, String::new());
+    }
+
+    let path = OsString::from_wide(&buffer[..length as usize]);
+    (process_id, path.to_string_lossy().into_owned())
+}
+
+// 收集窗口信息，图标标识使用可执行文件路径
+unsafe fn get_window_info(hwnd: HWND, activated_at: i64) -> WindowInfo {
+    let (process_id, path) = get_window_process(hwnd);
+    let process_name = Path::new(&path)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+
+    WindowInfo {
+        id: hwnd as WindowId,
+        title: get_window_title(hwnd),
+        process_id,
+        process_name,
+        icon_id: path,
+        activated_at,
+    }
+}
+
+// 任务栏上显示的窗口：可见、没有所有者、不是工具窗口
+unsafe fn is_app_window(hwnd: HWND) -> bool {
+    IsWindowVisible(hwnd) != 0
+        && GetWindow(hwnd, GW_OWNER).is_null()
+        && (GetWindowLongW(hwnd, GWL_EXSTYLE) as DWORD & WS_EX_TOOLWINDOW) == 0
+}
+
+// EnumWindows 回调，lparam 指向收集结果的 Vec
+unsafe extern "system" fn enum_windows_callback(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let windows = &mut *(lparam as *mut Vec<WindowInfo>);
+    if is_app_window(hwnd) {
+        let window = get_window_info(hwnd, history::activated_at(hwnd as WindowId));
+        if !history::is_excluded(&window) {
+            windows.push(window);
+        }
+    }
+    TRUE
+}
+
+// 定义事件钩子回调函数
+unsafe extern "system" fn event_hook_callback(
+    _h_win_event_hook: HWINEVENTHOOK,
+    event: DWORD,
+    hwnd: HWND,
+    _id_object: i32,
+    _id_child: i32,
+    _dw_event_thread: DWORD,
+    _dwms_event_time: DWORD,
+) {
+    if event == EVENT_SYSTEM_FOREGROUND && !hwnd.is_null() {
+        history::record(get_window_info(hwnd, chrono::Utc::now().timestamp_millis()));
+    }
+}
+
+fn clipboard_error(message: &str) -> PasteError {
+    PasteError::ClipboardFailed {
+        message: message.to_string(),
+    }
+}
+
+// 打开剪贴板，关闭时自动调用 CloseClipboard
+struct OpenedClipboard;
+
+impl OpenedClipboard {
+    // 剪贴板可能正被其他程序占用，失败时稍后重试
+    fn open() -> Result<Self, PasteError> {
+        for _ in 0..5 {
+            if unsafe { OpenClipboard(ptr::null_mut()) } != 0 {
+                return Ok(OpenedClipboard);
+            }
+            wait(20);
+        }
+        Err(clipboard_error("Failed to open clipboard"))
+    }
+
+    // 读取指定格式的原始数据
+    fn read(&self, format: UINT) -> Option<Vec<u8>> {
+        unsafe {
+            let handle = GetClipboardData(format);
+            if handle.is_null() {
+                return None;
+            }
+            let buffer = GlobalLock(handle) as *const u8;
+            if buffer.is_null() {
+                return None;
+            }
+            let data = std::slice::from_raw_parts(buffer, GlobalSize(handle)).to_vec();
+            GlobalUnlock(handle);
+            Some(data)
+        }
+    }
+
+    // 写入指定格式的原始数据，需先调用 EmptyClipboard
+    fn write(&self, format: UINT, data: &[u8]) -> Result<(), PasteError> {
+        unsafe {
+            let handle = GlobalAlloc(GMEM_MOVEABLE, data.len());
+            if handle.is_null() {
+                return Err(clipboard_error("Failed to allocate clipboard memory"));
+            }
+
+            let buffer = GlobalLock(handle) as *mut u8;
+            if buffer.is_null() {
+                GlobalFree(handle);
+                return Err(clipboard_error("Failed to lock clipboard memory"));
+            }
+            ptr::copy_nonoverlapping(data.as_ptr(), buffer, data.len());
+            GlobalUnlock(handle);
+
+            // 设置成功后内存归剪贴板所有，失败时需自行释放
+            if SetClipboardData(format, handle).is_null() {
+                GlobalFree(handle);
+                return Err(clipboard_error("Failed to set clipboard data"));
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for OpenedClipboard {
+    fn drop(&mut self) {
+        unsafe {
+            CloseClipboard();
+        }
+    }
+}
+
+// 浏览器和 Office 使用的 HTML 剪贴板格式
+fn html_clipboard_format() -> UINT {
+    let name: Vec<u16> = "HTML Format".encode_utf16().chain(Some(0)).collect();
+    unsafe { RegisterClipboardFormatW(name.as_ptr()) }
+}
+
+// CF_UNICODETEXT 数据为以 0 结尾的 UTF-16 文本
+fn decode_unicode_text(data: &[u8]) -> String {
+    let wide: Vec<u16> = data
+        .chunks_exact(2)
+        .map(|pair| u16::from_ne_bytes([pair[0], pair[1]]))
+        .take_while(|&c| c != 0)
+        .collect();
+    String::from_utf16_lossy(&wide)
+}
+
+fn encode_unicode_text(text: &str) -> Vec<u8> {
+    text.encode_utf16()
+        .chain(Some(0))
+        .flat_map(u16::to_ne_bytes)
+        .collect()
+}
+
+/// 基于 WinAPI 的实现
+pub struct WindowsPlatform;
+
+impl Platform for WindowsPlatform {
+    // 监听窗口切换
+    fn observe_foreground(&self) -> Result<(), PasteError> {
+        unsafe {
+            // 设置事件钩子
+            let hook = SetWinEventHook(
+                EVENT_SYSTEM_FOREGROUND,
+                EVENT_SYSTEM_FOREGROUND,
+                ptr::null_mut(),
+                Some(event_hook_callback),
+                0,
+                0,
+                WINEVENT_OUTOFCONTEXT,
+            );
+
+            if hook.is_null() {
+                return Err(PasteError::BackendUnavailable {
+                    message: "Failed to set foreground event hook".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+
+    // EnumWindows 按 Z 序从上到下枚举
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, PasteError> {
+        let mut windows: Vec<WindowInfo> = Vec::new();
+        let succeeded = unsafe {
+            EnumWindows(
+                Some(enum_windows_callback),
+                &mut windows as *mut Vec<WindowInfo> as LPARAM,
+            )
+        };
+        if succeeded == 0 {
+            return Err(PasteError::BackendUnavailable {
+                message: "EnumWindows failed".to_string(),
+            });
+        }
+        Ok(windows)
+    }
+
+    fn foreground_window(&self) -> Result<Option<WindowInfo>, PasteError> {
+        let hwnd = unsafe { GetForegroundWindow() };
+        if hwnd.is_null() {
+            return Ok(None);
+        }
+        let activated_at = chrono::Utc::now().timestamp_millis();
+        Ok(Some(unsafe { get_window_info(hwnd, activated_at) }))
+    }
+
+    fn window_info(&self, window: WindowId) -> Result<WindowInfo, PasteError> {
+        let hwnd = window as isize as HWND;
+        if unsafe { IsWindow(hwnd) } == 0 {
+            return Err(PasteError::FocusFailed {
+                message: "Window no longer exists".to_string(),
+            });
+        }
+        Ok(unsafe { get_window_info(hwnd, history::activated_at(window)) })
+    }
+
+    fn focus_window(&self, window: WindowId) -> Result<(), PasteError> {
+        let hwnd = window as isize as HWND;
+        if hwnd.is_null() || unsafe { SetForegroundWindow(hwnd) } == 0 {
+            return Err(PasteError::FocusFailed {
+                message: "SetForegroundWindow failed".to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    fn read_clipboard(&self) -> Result<ClipboardContents, PasteError> {
+        let clipboard = OpenedClipboard::open()?;
+        Ok(ClipboardContents {
+            text: clipboard
+                .read(CF_UNICODETEXT)
+                .map(|data| decode_unicode_text(&data)),
+            html: clipboard.read(html_clipboard_format()),
+        })
+    }
+
+    fn write_clipboard(&self, contents: &ClipboardContents) -> Result<(), PasteError> {
+        let clipboard = OpenedClipboard::open()?;
+        unsafe {
+            EmptyClipboard();
+        }
+        if let Some(text) = &contents.text {
+            clipboard.write(CF_UNICODETEXT, &encode_unicode_text(text))?;
+        }
+        if let Some(html) = &contents.html {
+            clipboard.write(html_clipboard_format(), html)?;
+        }
+        Ok(())
+    }
+
+    fn send_paste_keys(&self, keys: PasteKeys) -> Result<(), PasteError> {
+        let mut enigo =
+            Enigo::new(&Settings::default()).map_err(|e| PasteError::BackendUnavailable {
+                message: e.to_string(),
+            })?;
+        let modifier = match keys {
+            PasteKeys::ShiftInsert => Key::Shift,
+            PasteKeys::CtrlV => Key::Control,
+        };
+        let key = match keys {
+            // insert 的微软虚拟键码：https://learn.microsoft.com/en-us/windows/win32/inputdev/virtual-key-codes
+            PasteKeys::ShiftInsert => Key::Other(0x2D),
+            PasteKeys::CtrlV => Key::Unicode('v'),
+        };
+
+        let mut send = || -> enigo::InputResult<()> {
+            enigo.key(modifier, Press)?;
+            enigo.key(key, Click)?;
+            enigo.key(modifier, Release)
+        };
+        send().map_err(|e| PasteError::KeySendFailed {
+            message: e.to_string(),
+        })
+    }
+}
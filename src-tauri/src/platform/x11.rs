@@ -0,0 +1,607 @@
+use super::history::{self, WindowInfo};
+use super::{ClipboardContents, PasteError, PasteKeys, Platform, WindowId};
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use x11rb::connection::Connection;
+use x11rb::protocol::xproto::{
+    Atom, AtomEnum, ChangeWindowAttributesAux, ClientMessageEvent, ConnectionExt as _,
+    CreateWindowAux, EventMask, PropMode, SelectionNotifyEvent, SelectionRequestEvent, Window,
+    WindowClass, KEY_PRESS_EVENT, KEY_RELEASE_EVENT, SELECTION_NOTIFY_EVENT,
+};
+use x11rb::protocol::xtest::ConnectionExt as _;
+use x11rb::protocol::Event;
+use x11rb::rust_connection::RustConnection;
+use x11rb::wrapper::ConnectionExt as _;
+use x11rb::{CURRENT_TIME, NONE};
+
+/// 等待剪贴板所有者响应的最长时间
+const SELECTION_TIMEOUT: Duration = Duration::from_millis(500);
+
+// 键盘符号，见 X11/keysymdef.h
+const XK_SHIFT_L: u32 = 0xffe1;
+const XK_CONTROL_L: u32 = 0xffe3;
+const XK_INSERT: u32 = 0xff63;
+const XK_V: u32 = 0x0076;
+
+type X11Result<T> = Result<T, Box<dyn std::error::Error + Send + Sync>>;
+
+x11rb::atom_manager! {
+    Atoms: AtomsCookie {
+        _NET_ACTIVE_WINDOW,
+        _NET_CLIENT_LIST_STACKING,
+        _NET_WM_NAME,
+        _NET_WM_PID,
+        UTF8_STRING,
+        TEXT,
+        CLIPBOARD,
+        TARGETS,
+        INCR,
+        TEXT_HTML: b"text/html",
+        PASTE_PROPERTY: b"TEMPLATE_PASTE",
+    }
+}
+
+fn backend_unavailable(e: Box<dyn std::error::Error + Send + Sync>) -> PasteError {
+    PasteError::BackendUnavailable {
+        message: format!("X11: {}", e),
+    }
+}
+
+fn clipboard_failed(e: Box<dyn std::error::Error + Send + Sync>) -> PasteError {
+    PasteError::ClipboardFailed {
+        message: e.to_string(),
+    }
+}
+
+/// 与 X 服务器的连接
+struct Session {
+    conn: RustConnection,
+    root: Window,
+    atoms: Atoms,
+}
+
+impl Session {
+    fn connect() -> X11Result<Self> {
+        let (conn, screen_num) = x11rb::connect(None)?;
+        let root = conn.setup().roots[screen_num].root;
+        let atoms = Atoms::new(&conn)?.reply()?;
+        Ok(Session { conn, root, atoms })
+    }
+
+    /// 创建一个不可见的窗口，用于接收剪贴板数据或作为剪贴板所有者
+    fn create_hidden_window(&self) -> X11Result<Window> {
+        let window = self.conn.generate_id()?;
+        self.conn.create_window(
+            0,
+            window,
+            self.root,
+            0,
+            0,
+            1,
+            1,
+            0,
+            WindowClass::INPUT_ONLY,
+            0,
+            &CreateWindowAux::new(),
+        )?;
+        Ok(window)
+    }
+
+    /// 读取 EWMH 的当前活动窗口
+    fn active_window(&self) -> X11Result<Option<Window>> {
+        let reply = self
+            .conn
+            .get_property(
+                false,
+                self.root,
+                self.atoms._NET_ACTIVE_WINDOW,
+                AtomEnum::WINDOW,
+                0,
+                1,
+            )?
+            .reply()?;
+        Ok(reply
+            .value32()
+            .and_then(|mut windows| windows.next())
+            .filter(|&window| window != NONE))
+    }
+
+    /// 读取窗口标题，优先使用 UTF-8 的 _NET_WM_NAME
+    fn window_title(&self, window: Window) -> String {
+        let properties: [(Atom, Atom); 2] = [
+            (self.atoms._NET_WM_NAME, self.atoms.UTF8_STRING),
+            (AtomEnum::WM_NAME.into(), AtomEnum::STRING.into()),
+        ];
+        properties
+            .iter()
+            .find_map(|&(property, type_)| {
+                let reply = self
+                    .conn
+                    .get_property(false, window, property, type_, 0, 1024)
+                    .ok()?
+                    .reply()
+                    .ok()?;
+                (!reply.value.is_empty())
+                    .then(|| String::from_utf8_lossy(&reply.value).into_owned())
+            })
+            .unwrap_or_default()
+    }
+
+    /// 读取窗口所属的进程ID和进程名，窗口未设置 _NET_WM_PID 时为空
+    fn window_process(&self, window: Window) -> (u32, String) {
+        let process_id = self
+            .conn
+            .get_property(
+                false,
+                window,
+                self.atoms._NET_WM_PID,
+                AtomEnum::CARDINAL,
+                0,
+                1,
+            )
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| reply.value32().and_then(|mut values| values.next()))
+            .unwrap_or_default();
+        if process_id == 0 {
+            return (0, String::new());
+        }
+
+        let process_name = std::fs::read_to_string(format!("/proc/{}/comm", process_id))
+            .map(|name| name.trim_end().to_string())
+            .unwrap_or_default();
+        (process_id, process_name)
+    }
+
+    /// 读取 WM_CLASS 中的类名，同一程序的窗口相同
+    fn window_class(&self, window: Window) -> String {
+        self.conn
+            .get_property(false, window, AtomEnum::WM_CLASS, AtomEnum::STRING, 0, 256)
+            .ok()
+            .and_then(|cookie| cookie.reply().ok())
+            .and_then(|reply| {
+                // 内容为以 0 分隔的实例名和类名
+                reply
+                    .value
+                    .split(|&byte| byte == 0)
+                    .nth(1)
+                    .map(|class| String::from_utf8_lossy(class).into_owned())
+            })
+            .unwrap_or_default()
+    }
+
+    fn window_info(&self, window: Window, activated_at: i64) -> WindowInfo {
+        let (process_id, process_name) = self.window_process(window);
+        WindowInfo {
+            id: WindowId::from(window),
+            title: self.window_title(window),
+            process_id,
+            process_name,
+            icon_id: self.window_class(window),
+            activated_at,
+        }
+    }
+
+    /// 读取窗口管理器管理的顶层窗口，从上到下排列
+    fn client_windows(&self) -> X11Result<Vec<Window>> {
+        let reply = self
+            .conn
+            .get_property(
+                false,
+                self.root,
+                self.atoms._NET_CLIENT_LIST_STACKING,
+                AtomEnum::WINDOW,
+                0,
+                u32::MAX,
+            )?
+            .reply()?;
+        // _NET_CLIENT_LIST_STACKING 按从下到上的顺序排列
+        let mut windows: Vec<Window> = reply
+            .value32()
+            .map(|windows| windows.collect())
+            .unwrap_or_default();
+        windows.reverse();
+        Ok(windows)
+    }
+
+    /// 监听根窗口上 _NET_ACTIVE_WINDOW 的变化，记录切换到前台的窗口
+    fn observe_active_window(&self) -> X11Result<()> {
+        self.conn
+            .change_window_attributes(
+                self.root,
+                &ChangeWindowAttributesAux::new().event_mask(EventMask::PROPERTY_CHANGE),
+            )?
+            .check()?;
+
+        loop {
+            let Event::PropertyNotify(event) = self.conn.wait_for_event()? else {
+                continue;
+            };
+            if event.atom != self.atoms._NET_ACTIVE_WINDOW {
+                continue;
+            }
+            let Some(window) = self.active_window()? else {
+                continue;
+            };
+            history::record(self.window_info(window, chrono::Utc::now().timestamp_millis()));
+        }
+    }
+
+    /// 查找键盘符号对应的键码
+    fn keycode(&self, keysym: u32) -> X11Result<u8> {
+        let setup = self.conn.setup();
+        let (min, max) = (setup.min_keycode, setup.max_keycode);
+        let mapping = self
+            .conn
+            .get_keyboard_mapping(min, max - min + 1)?
+            .reply()?;
+        let per_keycode = usize::from(mapping.keysyms_per_keycode).max(1);
+        mapping
+            .keysyms
+            .chunks(per_keycode)
+            .position(|keysyms| keysyms.contains(&keysym))
+            .map(|index| min + index as u8)
+            .ok_or_else(|| format!("No keycode for keysym {:#x}", keysym).into())
+    }
+
+    /// 通过 XTest 发送组合键
+    fn send_chord(&self, modifier: u32, key: u32) -> X11Result<()> {
+        let modifier = self.keycode(modifier)?;
+        let key = self.keycode(key)?;
+        for (type_, keycode) in [
+            (KEY_PRESS_EVENT, modifier),
+            (KEY_PRESS_EVENT, key),
+            (KEY_RELEASE_EVENT, key),
+            (KEY_RELEASE_EVENT, modifier),
+        ] {
+            self.conn
+                .xtest_fake_input(type_, keycode, CURRENT_TIME, self.root, 0, 0, 0)?;
+        }
+        // 往返一次，确保按键已被服务器处理
+        self.conn.get_input_focus()?.reply()?;
+        Ok(())
+    }
+
+    /// 请求剪贴板所有者把内容转换为指定格式，超时或不支持该格式时返回 None
+    fn read_selection(&self, window: Window, target: Atom) -> X11Result<Option<Vec<u8>>> {
+        self.conn.convert_selection(
+            window,
+            self.atoms.CLIPBOARD,
+            target,
+            self.atoms.PASTE_PROPERTY,
+            CURRENT_TIME,
+        )?;
+        self.conn.flush()?;
+
+        let deadline = Instant::now() + SELECTION_TIMEOUT;
+        loop {
+            match self.conn.poll_for_event()? {
+                Some(Event::SelectionNotify(event)) if event.requestor == window => {
+                    if event.property == NONE {
+                        return Ok(None);
+                    }
+                    let reply = self
+                        .conn
+                        .get_property(
+                            true,
+                            window,
+                            self.atoms.PASTE_PROPERTY,
+                            AtomEnum::ANY,
+                            0,
+                            u32::MAX,
+                        )?
+                        .reply()?;
+                    // 大块数据使用 INCR 分段传输，不予处理
+                    if reply.type_ == self.atoms.INCR {
+                        return Ok(None);
+                    }
+                    return Ok(Some(reply.value));
+                }
+                Some(_) => {}
+                None if Instant::now() >= deadline => return Ok(None),
+                None => thread::sleep(Duration::from_millis(10)),
+            }
+        }
+    }
+
+    /// 响应其他程序读取剪贴板的请求，返回写入数据的属性，不支持的格式返回 NONE
+    fn answer_request(
+        &self,
+        request: &SelectionRequestEvent,
+        contents: &ClipboardContents,
+    ) -> X11Result<Atom> {
+        // 旧客户端可能不指定属性，此时使用目标格式作为属性名
+        let property = if request.property == NONE {
+            request.target
+        } else {
+            request.property
+        };
+        let text_targets = [
+            self.atoms.UTF8_STRING,
+            self.atoms.TEXT,
+            AtomEnum::STRING.into(),
+        ];
+        let text = contents
+            .text
+            .as_deref()
+            .filter(|_| text_targets.contains(&request.target));
+        let html = contents
+            .html
+            .as_deref()
+            .filter(|_| request.target == self.atoms.TEXT_HTML);
+
+        if request.target == self.atoms.TARGETS {
+            let mut targets = vec![self.atoms.TARGETS];
+            if contents.text.is_some() {
+                targets.extend(text_targets);
+            }
+            if contents.html.is_some() {
+                targets.push(self.atoms.TEXT_HTML);
+            }
+            self.conn.change_property32(
+                PropMode::REPLACE,
+                request.requestor,
+                property,
+                AtomEnum::ATOM,
+                &targets,
+            )?;
+        } else if let Some(text) = text {
+            self.conn.change_property8(
+                PropMode::REPLACE,
+                request.requestor,
+                property,
+                self.atoms.UTF8_STRING,
+                text.as_bytes(),
+            )?;
+        } else if let Some(html) = html {
+            self.conn.change_property8(
+                PropMode::REPLACE,
+                request.requestor,
+                property,
+                self.atoms.TEXT_HTML,
+                html,
+            )?;
+        } else {
+            return Ok(NONE);
+        }
+        Ok(property)
+    }
+
+    /// 通过 EWMH 请求窗口管理器激活窗口
+    fn activate(&self, window: Window) -> X11Result<()> {
+        // 来源标记 2 表示由任务栏等工具发起，窗口管理器不会拒绝
+        let event = ClientMessageEvent::new(
+            32,
+            window,
+            self.atoms._NET_ACTIVE_WINDOW,
+            [2, CURRENT_TIME, 0, 0, 0],
+        );
+        self.conn.send_event(
+            false,
+            self.root,
+            EventMask::SUBSTRUCTURE_REDIRECT | EventMask::SUBSTRUCTURE_NOTIFY,
+            event,
+        )?;
+        self.conn.flush()?;
+        Ok(())
+    }
+
+    /// 读取剪贴板中的文本和 HTML 内容
+    fn read_clipboard(&self) -> X11Result<ClipboardContents> {
+        let window = self.create_hidden_window()?;
+        let text = self.read_selection(window, self.atoms.UTF8_STRING)?;
+        let html = self.read_selection(window, self.atoms.TEXT_HTML)?;
+        Ok(ClipboardContents {
+            text: text.map(|text| String::from_utf8_lossy(&text).into_owned()),
+            html,
+        })
+    }
+
+    /// 创建窗口并取得剪贴板所有权，返回该窗口
+    fn take_clipboard_ownership(&self) -> X11Result<Window> {
+        let window = self.create_hidden_window()?;
+        self.conn
+            .set_selection_owner(window, self.atoms.CLIPBOARD, CURRENT_TIME)?;
+        let owner = self
+            .conn
+            .get_selection_owner(self.atoms.CLIPBOARD)?
+            .reply()?
+            .owner;
+        if owner != window {
+            return Err("Failed to take clipboard ownership".into());
+        }
+        Ok(window)
+    }
+
+    /// 作为剪贴板所有者提供内容，直到其他程序取得剪贴板
+    fn serve_selection(&self, window: Window, contents: &ClipboardContents) -> X11Result<()> {
+        loop {
+            match self.conn.wait_for_event()? {
+                Event::SelectionRequest(request) => {
+                    let property = self.answer_request(&request, contents)?;
+                    let notify = SelectionNotifyEvent {
+                        response_type: SELECTION_NOTIFY_EVENT,
+                        sequence: 0,
+                        time: request.time,
+                        requestor: request.requestor,
+                        selection: request.selection,
+                        target: request.target,
+                        property,
+                    };
+                    self.conn
+                        .send_event(false, request.requestor, EventMask::NO_EVENT, notify)?;
+                    self.conn.flush()?;
+                }
+                Event::SelectionClear(event) if event.owner == window => return Ok(()),
+                _ => {}
+            }
+        }
+    }
+}
+
+/// 基于 X11 的实现：通过 EWMH 记录和切换活动窗口，通过 XTest 发送按键
+///
+/// 需要支持 EWMH 的窗口管理器，可在 Xvfb 中运行。
+pub struct X11Platform;
+
+impl Platform for X11Platform {
+    fn observe_foreground(&self) -> Result<(), PasteError> {
+        let session = Session::connect().map_err(backend_unavailable)?;
+        thread::spawn(move || {
+            if let Err(e) = session.observe_active_window() {
+                log::error!("监听活动窗口失败: {}", e);
+            }
+        });
+        Ok(())
+    }
+
+    fn list_windows(&self) -> Result<Vec<WindowInfo>, PasteError> {
+        let session = Session::connect().map_err(backend_unavailable)?;
+        let windows = session.client_windows().map_err(backend_unavailable)?;
+        Ok(windows
+            .into_iter()
+            .map(|window| {
+                session.window_info(window, history::activated_at(WindowId::from(window)))
+            })
+            .filter(|window| !history::is_excluded(window))
+            .collect())
+    }
+
+    fn foreground_window(&self) -> Result<Option<WindowInfo>, PasteError> {
+        let session = Session::connect().map_err(backend_unavailable)?;
+        let window = session.active_window().map_err(backend_unavailable)?;
+        let activated_at = chrono::Utc::now().timestamp_millis();
+        Ok(window.map(|window| session.window_info(window, activated_at)))
+    }
+
+    fn window_info(&self, window: WindowId) -> Result<WindowInfo, PasteError> {
+        let id = Window::try_from(window).map_err(|e| PasteError::FocusFailed {
+            message: e.to_string(),
+        })?;
+        let session = Session::connect().map_err(backend_unavailable)?;
+        Ok(session.window_info(id, history::activated_at(window)))
+    }
+
+    fn focus_window(&self, window: WindowId) -> Result<(), PasteError> {
+        let focus_failed = |e: &dyn std::fmt::Display| PasteError::FocusFailed {
+            message: e.to_string(),
+        };
+        let window = Window::try_from(window).map_err(|e| focus_failed(&e))?;
+        let session = Session::connect().map_err(backend_unavailable)?;
+        session.activate(window).map_err(|e| focus_failed(&e))
+    }
+
+    fn read_clipboard(&self) -> Result<ClipboardContents, PasteError> {
+        let session = Session::connect().map_err(backend_unavailable)?;
+        session.read_clipboard().map_err(clipboard_failed)
+    }
+
+    fn write_clipboard(&self, contents: &ClipboardContents) -> Result<(), PasteError> {
+        let session = Session::connect().map_err(backend_unavailable)?;
+        let window = session
+            .take_clipboard_ownership()
+            .map_err(clipboard_failed)?;
+
+        // X11 的剪贴板由所有者按需提供内容，需要保持连接直到其他程序取得剪贴板
+        let contents = contents.clone();
+        thread::spawn(move || {
+            if let Err(e) = session.serve_selection(window, &contents) {
+                log::warn!("提供剪贴板内容失败: {}", e);
+            }
+        });
+        Ok(())
+    }
+
+    fn send_paste_keys(&self, keys: PasteKeys) -> Result<(), PasteError> {
+        let (modifier, key) = match keys {
+            PasteKeys::ShiftInsert => (XK_SHIFT_L, XK_INSERT),
+            PasteKeys::CtrlV => (XK_CONTROL_L, XK_V),
+        };
+        let session = Session::connect().map_err(backend_unavailable)?;
+        session
+            .send_chord(modifier, key)
+            .map_err(|e| PasteError::KeySendFailed {
+                message: e.to_string(),
+            })
+    }
+}
+
+// 冒烟测试需要 X 服务器，并且会改写剪贴板，只在设置了 TEMPLATE_X11_TESTS 时运行：
+//
+//     TEMPLATE_X11_TESTS=1 xvfb-run cargo test x11
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn enabled() -> bool {
+        let enabled = std::env::var_os("TEMPLATE_X11_TESTS").is_some();
+        if !enabled {
+            eprintln!("跳过 X11 测试：未设置 TEMPLATE_X11_TESTS");
+        }
+        enabled
+    }
+
+    #[test]
+    fn reads_window_properties() {
+        if !enabled() {
+            return;
+        }
+        let session = Session::connect().expect("无法连接 X 服务器");
+        let window = session.create_hidden_window().unwrap();
+        session
+            .conn
+            .change_property8(
+                PropMode::REPLACE,
+                window,
+                session.atoms._NET_WM_NAME,
+                session.atoms.UTF8_STRING,
+                "病程记录".as_bytes(),
+            )
+            .unwrap();
+        session
+            .conn
+            .change_property8(
+                PropMode::REPLACE,
+                window,
+                AtomEnum::WM_CLASS,
+                AtomEnum::STRING,
+                b"emr\0Emr\0",
+            )
+            .unwrap();
+        session.conn.flush().unwrap();
+
+        let info = session.window_info(window, 42);
+        assert_eq!(info.id, WindowId::from(window));
+        assert_eq!(info.title, "病程记录");
+        assert_eq!(info.icon_id, "Emr");
+        assert_eq!(info.process_id, 0);
+        assert_eq!(info.activated_at, 42);
+    }
+
+    #[test]
+    fn clipboard_round_trip() {
+        if !enabled() {
+            return;
+        }
+        let contents = ClipboardContents {
+            text: Some("否认药物过敏史".to_string()),
+            html: Some("<b>否认</b>".as_bytes().to_vec()),
+        };
+        X11Platform.write_clipboard(&contents).unwrap();
+
+        let read = X11Platform.read_clipboard().unwrap();
+        assert_eq!(read.text, contents.text);
+        assert_eq!(read.html, contents.html);
+    }
+
+    #[test]
+    fn sends_paste_keys() {
+        if !enabled() {
+            return;
+        }
+        X11Platform.send_paste_keys(PasteKeys::ShiftInsert).unwrap();
+        X11Platform.send_paste_keys(PasteKeys::CtrlV).unwrap();
+    }
+}
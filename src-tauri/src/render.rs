@@ -0,0 +1,746 @@
+use crate::database::{Template, TemplateSection};
+use crate::expression::{self, Expr, Value, VariableUsage};
+use crate::format::{self, FormatProfile};
+use crate::validation::FieldError;
+use chrono::format::{Fixed, Item, Numeric, StrftimeItems};
+use chrono::{DateTime, Datelike, Local, NaiveDate};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write;
+
+/// 内置变量，渲染时自动填充
+const BUILTIN_VARIABLES: &[&str] = &["今天", "现在", "时间", "年", "月", "日", "星期"];
+
+/// 光标标记，粘贴后光标移动到标记所在位置
+pub const CURSOR_MARKER: &str = "{{cursor}}";
+
+/// 日期字段提交值和默认输出的格式
+const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// 模板中的变量
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateVariable {
+    pub name: String,
+    /// 是否为内置变量（如当前日期），内置变量无需填写
+    pub builtin: bool,
+}
+
+/// 填写字段的类型
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum FieldType {
+    Text,
+    Number,
+    Select,
+    Date,
+}
+
+/// 由占位符声明的填写字段
+///
+/// 语法为 `{{名称:类型:参数:默认值}}`，类型和之后的部分均可省略：
+/// `{{体温:number:℃}}` 的参数为单位，`{{性别:select:男|女}}` 的参数为选项，
+/// `{{发病日期:date:%Y年%m月%d日}}` 的参数为输出格式。
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FormField {
+    pub name: String,
+    pub field_type: FieldType,
+    pub unit: Option<String>,
+    pub options: Vec<String>,
+    pub format: Option<String>,
+    pub default: Option<String>,
+}
+
+/// 渲染后的模板
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderedTemplate {
+    pub sections: Vec<TemplateSection>,
+    /// 按格式方案拼接后的完整文本
+    pub text: String,
+}
+
+/// 渲染模板失败的原因
+#[derive(Debug, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum RenderError {
+    /// 填写的值未通过校验，按字段名列出错误
+    Validation { errors: Vec<FieldError> },
+    /// 其他错误，如模板不存在、数据库错误
+    Failed { message: String },
+}
+
+impl From<String> for RenderError {
+    fn from(message: String) -> Self {
+        RenderError::Failed { message }
+    }
+}
+
+impl From<&str> for RenderError {
+    fn from(message: &str) -> Self {
+        RenderError::Failed {
+            message: message.to_string(),
+        }
+    }
+}
+
+/// 解析后的内容片段
+#[derive(Debug, Clone)]
+enum Node {
+    Text(String),
+    /// 占位符，`raw` 为包含花括号的原始文本
+    Placeholder {
+        field: FormField,
+        raw: String,
+    },
+    /// 计算表达式 `{{= 表达式}}`
+    Expression {
+        expr: Expr,
+        raw: String,
+    },
+    /// 条件块 `{{#if 条件}}…{{else if 条件}}…{{else}}…{{/if}}`
+    Condition {
+        branches: Vec<(Expr, Vec<Node>)>,
+        otherwise: Vec<Node>,
+    },
+}
+
+/// 解析占位符内部的声明
+///
+/// 日期格式本身可能含有 `:`，因此日期字段只有最后一段为空或为 `YYYY-MM-DD` 日期时才视为默认值，
+/// 其余部分整体作为格式。
+fn parse_field(spec: &str) -> FormField {
+    let mut parts = spec.splitn(3, ':').map(str::trim);
+    let name = parts.next().unwrap_or_default().to_string();
+    let field_type = match parts.next().unwrap_or_default() {
+        "number" => FieldType::Number,
+        "select" => FieldType::Select,
+        "date" => FieldType::Date,
+        _ => FieldType::Text,
+    };
+    let rest = parts.next().unwrap_or_default();
+    let (argument, default) = match (field_type, rest.rsplit_once(':')) {
+        (FieldType::Date, Some((format, default)))
+            if default.trim().is_empty()
+                || NaiveDate::parse_from_str(default.trim(), DATE_FORMAT).is_ok() =>
+        {
+            (format.trim(), default.trim())
+        }
+        (FieldType::Date, _) => (rest, ""),
+        _ => match rest.split_once(':') {
+            Some((argument, default)) => (argument.trim(), default.trim()),
+            None => (rest, ""),
+        },
+    };
+    let argument = Some(argument).filter(|arg| !arg.is_empty());
+    let default = Some(default)
+        .filter(|value| !value.is_empty())
+        .map(str::to_string);
+
+    let mut field = FormField {
+        name,
+        field_type,
+        unit: None,
+        options: Vec::new(),
+        format: None,
+        default,
+    };
+    match (field_type, argument) {
+        (FieldType::Number, Some(unit)) => field.unit = Some(unit.to_string()),
+        (FieldType::Select, Some(options)) => {
+            field.options = options
+                .split('|')
+                .map(str::trim)
+                .filter(|option| !option.is_empty())
+                .map(str::to_string)
+                .collect();
+        }
+        (FieldType::Date, Some(format)) if is_valid_date_format(format) => {
+            field.format = Some(format.to_string());
+        }
+        _ => {}
+    }
+    field
+}
+
+/// 尚未闭合的条件块
+struct OpenCondition {
+    branches: Vec<(Expr, Vec<Node>)>,
+    otherwise: Option<Vec<Node>>,
+}
+
+impl OpenCondition {
+    fn nodes_mut(&mut self) -> &mut Vec<Node> {
+        match &mut self.otherwise {
+            Some(nodes) => nodes,
+            None => &mut self.branches.last_mut().expect("condition has a branch").1,
+        }
+    }
+
+    fn close(self) -> Node {
+        Node::Condition {
+            branches: self.branches,
+            otherwise: self.otherwise.unwrap_or_default(),
+        }
+    }
+}
+
+/// 去掉标签开头的关键字，关键字之后必须是空白或标签结尾，避免把 `{{#iffy}}` 当作 `#if`
+fn strip_keyword<'a>(tag: &'a str, keyword: &str) -> Option<&'a str> {
+    let rest = tag.strip_prefix(keyword)?;
+    (rest.is_empty() || rest.starts_with(char::is_whitespace)).then_some(rest)
+}
+
+/// 把章节内容解析为语法树，同时返回语法错误
+///
+/// 出错时尽量保留原文：未闭合的 `{{` 、无法解析的标签都按普通文本处理，缺少 `{{/if}}` 的条件块在末尾自动闭合。
+fn parse(content: &str) -> (Vec<Node>, Vec<String>) {
+    let mut root = Vec::new();
+    let mut stack: Vec<OpenCondition> = Vec::new();
+    let mut errors = Vec::new();
+    let mut rest = content;
+
+    fn target<'a>(root: &'a mut Vec<Node>, stack: &'a mut [OpenCondition]) -> &'a mut Vec<Node> {
+        match stack.last_mut() {
+            Some(open) => open.nodes_mut(),
+            None => root,
+        }
+    }
+
+    while let Some((start, len)) = rest
+        .find("{{")
+        .and_then(|start| rest[start + 2..].find("}}").map(|len| (start, len)))
+    {
+        if start > 0 {
+            target(&mut root, &mut stack).push(Node::Text(rest[..start].to_string()));
+        }
+        let end = start + 2 + len + 2;
+        let raw = &rest[start..end];
+        let tag = rest[start + 2..start + 2 + len].trim();
+        rest = &rest[end..];
+
+        if let Some(condition) = strip_keyword(tag, "#if") {
+            match expression::parse(condition) {
+                Ok(expr) => stack.push(OpenCondition {
+                    branches: vec![(expr, Vec::new())],
+                    otherwise: None,
+                }),
+                Err(e) => {
+                    errors.push(format!("{}：{}", raw, e));
+                    target(&mut root, &mut stack).push(Node::Text(raw.to_string()));
+                }
+            }
+        } else if let Some(condition) =
+            strip_keyword(tag, "else").and_then(|rest| strip_keyword(rest.trim_start(), "if"))
+        {
+            match (stack.last_mut(), expression::parse(condition)) {
+                (Some(open), Ok(expr)) if open.otherwise.is_none() => {
+                    open.branches.push((expr, Vec::new()));
+                }
+                (_, Err(e)) => {
+                    errors.push(format!("{}：{}", raw, e));
+                    target(&mut root, &mut stack).push(Node::Text(raw.to_string()));
+                }
+                _ => {
+                    errors.push(format!("{} 没有对应的 {{{{#if}}}}", raw));
+                    target(&mut root, &mut stack).push(Node::Text(raw.to_string()));
+                }
+            }
+        } else if tag == "else" {
+            match stack.last_mut() {
+                Some(open) if open.otherwise.is_none() => open.otherwise = Some(Vec::new()),
+                _ => {
+                    errors.push(format!("{} 没有对应的 {{{{#if}}}}", raw));
+                    target(&mut root, &mut stack).push(Node::Text(raw.to_string()));
+                }
+            }
+        } else if tag == "/if" {
+            match stack.pop() {
+                Some(open) => target(&mut root, &mut stack).push(open.close()),
+                None => {
+                    errors.push(format!("{} 没有对应的 {{{{#if}}}}", raw));
+                    root.push(Node::Text(raw.to_string()));
+                }
+            }
+        } else if tag.starts_with('#') {
+            errors.push(format!("{}：未知的块标签", raw));
+            target(&mut root, &mut stack).push(Node::Text(raw.to_string()));
+        } else if tag == "cursor" {
+            // 光标标记原样保留到渲染结果中，由粘贴时处理
+            target(&mut root, &mut stack).push(Node::Text(CURSOR_MARKER.to_string()));
+        } else if let Some(source) = tag.strip_prefix('=') {
+            match expression::parse(source) {
+                Ok(expr) => target(&mut root, &mut stack).push(Node::Expression {
+                    expr,
+                    raw: raw.to_string(),
+                }),
+                Err(e) => {
+                    errors.push(format!("{}：{}", raw, e));
+                    target(&mut root, &mut stack).push(Node::Text(raw.to_string()));
+                }
+            }
+        } else {
+            let field = parse_field(tag);
+            let node = if field.name.is_empty() {
+                Node::Text(raw.to_string())
+            } else {
+                Node::Placeholder {
+                    field,
+                    raw: raw.to_string(),
+                }
+            };
+            target(&mut root, &mut stack).push(node);
+        }
+    }
+
+    if !rest.is_empty() {
+        target(&mut root, &mut stack).push(Node::Text(rest.to_string()));
+    }
+    if !stack.is_empty() {
+        errors.push(format!(
+            "有 {} 个 {{{{#if}}}} 缺少 {{{{/if}}}}",
+            stack.len()
+        ));
+    }
+    while let Some(open) = stack.pop() {
+        target(&mut root, &mut stack).push(open.close());
+    }
+
+    (root, errors)
+}
+
+/// 检查章节内容的模板语法，返回错误描述
+pub fn syntax_errors(content: &str) -> Vec<String> {
+    parse(content).1
+}
+
+/// 去除文本中的光标标记，返回去除后的文本和光标之后的字符数
+///
+/// 有多个标记时以第一个为准。`\r\n` 计为一个字符，与按一次左方向键移动的距离一致。
+pub fn take_cursor(text: &str) -> (String, Option<usize>) {
+    let offset = text.find(CURSOR_MARKER).map(|position| {
+        let after = text[position..].replace(CURSOR_MARKER, "");
+        after.chars().count() - after.matches("\r\n").count()
+    });
+    (text.replace(CURSOR_MARKER, ""), offset)
+}
+
+/// 检查日期格式字符串是否合法
+///
+/// 日期字段没有时间和时区，格式中出现 `%H`、`%z` 等说明符时在输出时会出错，一并视为非法。
+fn is_valid_date_format(format: &str) -> bool {
+    StrftimeItems::new(format).all(|item| match item {
+        Item::Literal(_) | Item::OwnedLiteral(_) | Item::Space(_) | Item::OwnedSpace(_) => true,
+        Item::Numeric(numeric, _) => matches!(
+            numeric,
+            Numeric::Year
+                | Numeric::YearDiv100
+                | Numeric::YearMod100
+                | Numeric::IsoYear
+                | Numeric::IsoYearDiv100
+                | Numeric::IsoYearMod100
+                | Numeric::Month
+                | Numeric::Day
+                | Numeric::WeekFromSun
+                | Numeric::WeekFromMon
+                | Numeric::IsoWeek
+                | Numeric::NumDaysFromSun
+                | Numeric::WeekdayFromMon
+                | Numeric::Ordinal
+        ),
+        Item::Fixed(fixed) => matches!(
+            fixed,
+            Fixed::ShortMonthName
+                | Fixed::LongMonthName
+                | Fixed::ShortWeekdayName
+                | Fixed::LongWeekdayName
+        ),
+        _ => false,
+    })
+}
+
+/// 计算内置变量的值
+fn builtin_value(name: &str, now: &DateTime<Local>) -> Option<String> {
+    let value = match name {
+        "今天" => now.format(DATE_FORMAT).to_string(),
+        "现在" => now.format("%Y-%m-%d %H:%M").to_string(),
+        "时间" => now.format("%H:%M").to_string(),
+        "年" => now.year().to_string(),
+        "月" => now.month().to_string(),
+        "日" => now.day().to_string(),
+        "星期" => {
+            const WEEKDAYS: [&str; 7] = ["一", "二", "三", "四", "五", "六", "日"];
+            format!(
+                "星期{}",
+                WEEKDAYS[now.weekday().num_days_from_monday() as usize]
+            )
+        }
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// 按字段类型格式化填写的值
+fn format_value(field: &FormField, value: &str) -> String {
+    match field.field_type {
+        FieldType::Number => match &field.unit {
+            Some(unit) if !value.is_empty() => format!("{}{}", value, unit),
+            _ => value.to_string(),
+        },
+        FieldType::Date => match (&field.format, NaiveDate::parse_from_str(value, DATE_FORMAT)) {
+            // 格式化出错时 `to_string` 会 panic，写入字符串可以拿到错误并退回原始值
+            (Some(format), Ok(date)) => {
+                let mut output = String::new();
+                match write!(output, "{}", date.format(format)) {
+                    Ok(()) => output,
+                    Err(_) => value.to_string(),
+                }
+            }
+            _ => value.to_string(),
+        },
+        FieldType::Text | FieldType::Select => value.to_string(),
+    }
+}
+
+/// 按文档顺序遍历语法树
+fn walk<'a>(nodes: &'a [Node], visit: &mut dyn FnMut(&'a Node)) {
+    for node in nodes {
+        visit(node);
+        if let Node::Condition {
+            branches,
+            otherwise,
+        } = node
+        {
+            for (_, children) in branches {
+                walk(children, visit);
+            }
+            walk(otherwise, visit);
+        }
+    }
+}
+
+/// 由表达式中的用途推断字段
+fn inferred_field(name: String, usage: VariableUsage) -> FormField {
+    FormField {
+        name,
+        field_type: match usage {
+            VariableUsage::Text => FieldType::Text,
+            VariableUsage::Number => FieldType::Number,
+            VariableUsage::Date => FieldType::Date,
+        },
+        unit: None,
+        options: Vec::new(),
+        format: None,
+        default: None,
+    }
+}
+
+/// 收集模板引用的所有字段，按首次出现的顺序去重
+///
+/// 占位符中的声明优先于从条件、表达式中推断出的字段；多个占位符声明同一字段时以第一个为准。
+fn collect_fields(template: &Template) -> Vec<FormField> {
+    let mut fields: Vec<(FormField, bool)> = Vec::new();
+    let mut add = |field: FormField, declared: bool| match fields
+        .iter_mut()
+        .find(|(existing, _)| existing.name == field.name)
+    {
+        Some(entry) if declared && !entry.1 => *entry = (field, true),
+        Some(_) => {}
+        None => fields.push((field, declared)),
+    };
+
+    for section in &template.sections {
+        let (nodes, _) = parse(&section.content);
+        walk(&nodes, &mut |node| match node {
+            Node::Text(_) => {}
+            Node::Placeholder { field, .. } => add(field.clone(), true),
+            Node::Expression { expr, .. } => {
+                for (name, usage) in expr.variables() {
+                    add(inferred_field(name, usage), false);
+                }
+            }
+            Node::Condition { branches, .. } => {
+                for (expr, _) in branches {
+                    for (name, usage) in expr.variables() {
+                        add(inferred_field(name, usage), false);
+                    }
+                }
+            }
+        });
+    }
+
+    fields.into_iter().map(|(field, _)| field).collect()
+}
+
+/// 列出模板中出现的变量，按首次出现的顺序去重
+pub fn list_variables(template: &Template) -> Vec<TemplateVariable> {
+    collect_fields(template)
+        .into_iter()
+        .map(|field| TemplateVariable {
+            builtin: BUILTIN_VARIABLES.contains(&field.name.as_str()),
+            name: field.name,
+        })
+        .collect()
+}
+
+/// 生成模板的填写表单，不含内置变量
+pub fn form_schema(template: &Template) -> Vec<FormField> {
+    collect_fields(template)
+        .into_iter()
+        .filter(|field| !BUILTIN_VARIABLES.contains(&field.name.as_str()))
+        .collect()
+}
+
+/// 校验填写的值，空值视为未填写
+pub fn validate_values(fields: &[FormField], values: &HashMap<String, String>) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    for field in fields {
+        let Some(value) = values.get(&field.name).map(|value| value.trim()) else {
+            continue;
+        };
+        if value.is_empty() {
+            continue;
+        }
+
+        let message = match field.field_type {
+            FieldType::Number if value.parse::<f64>().is_err() => "请输入数字".to_string(),
+            FieldType::Select if !field.options.iter().any(|option| option == value) => {
+                format!("请从以下选项中选择：{}", field.options.join("、"))
+            }
+            FieldType::Date if NaiveDate::parse_from_str(value, DATE_FORMAT).is_err() => {
+                "日期格式应为 YYYY-MM-DD".to_string()
+            }
+            _ => continue,
+        };
+        errors.push(FieldError {
+            field: field.name.clone(),
+            message,
+        });
+    }
+    errors
+}
+
+/// 渲染上下文
+struct RenderContext<'a> {
+    fields: &'a [FormField],
+    values: &'a HashMap<String, String>,
+    now: DateTime<Local>,
+}
+
+impl RenderContext<'_> {
+    /// 读取变量的值：依次使用填写的值、字段默认值、内置变量
+    fn value_of(&self, field: &FormField) -> Option<String> {
+        self.values
+            .get(&field.name)
+            .cloned()
+            .or_else(|| field.default.clone())
+            .or_else(|| builtin_value(&field.name, &self.now))
+    }
+
+    /// 查找字段声明，未在表单中声明的（如内置变量）使用占位符自身的声明
+    fn declared<'f>(&'f self, field: &'f FormField) -> &'f FormField {
+        self.fields
+            .iter()
+            .find(|declared| declared.name == field.name)
+            .unwrap_or(field)
+    }
+
+    fn lookup(&self, name: &str) -> Value {
+        let field = self
+            .fields
+            .iter()
+            .find(|field| field.name == name)
+            .cloned()
+            .unwrap_or_else(|| inferred_field(name.to_string(), VariableUsage::Text));
+        self.value_of(&field)
+            .map(Value::Text)
+            .unwrap_or(Value::Null)
+    }
+
+    /// 渲染节点，没有值的占位符和无法计算的表达式保留原样
+    fn render_nodes(&self, nodes: &[Node], output: &mut String) {
+        for node in nodes {
+            match node {
+                Node::Text(text) => output.push_str(text),
+                Node::Placeholder { field, raw } => {
+                    let field = self.declared(field);
+                    match self.value_of(field) {
+                        Some(value) => output.push_str(&format_value(field, &value)),
+                        None => output.push_str(raw),
+                    }
+                }
+                Node::Expression { expr, raw } => match expr.eval(&|name| self.lookup(name)) {
+                    Ok(Value::Null) | Err(_) => output.push_str(raw),
+                    Ok(value) => output.push_str(&value.to_string()),
+                },
+                Node::Condition {
+                    branches,
+                    otherwise,
+                } => {
+                    let matched = branches.iter().find(|(condition, _)| {
+                        condition
+                            .eval(&|name| self.lookup(name))
+                            .is_ok_and(|value| value.is_truthy())
+                    });
+                    match matched {
+                        Some((_, children)) => self.render_nodes(children, output),
+                        None => self.render_nodes(otherwise, output),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// 渲染整个模板并按格式方案拼接文本，章节引用需事先解析
+pub fn render_template(
+    template: &Template,
+    values: &HashMap<String, String>,
+    profile: &FormatProfile,
+) -> RenderedTemplate {
+    let fields = form_schema(template);
+    let context = RenderContext {
+        fields: &fields,
+        values,
+        now: Local::now(),
+    };
+    let sections: Vec<TemplateSection> = template
+        .sections
+        .iter()
+        .map(|section| {
+            let mut content = String::with_capacity(section.content.len());
+            context.render_nodes(&parse(&section.content).0, &mut content);
+            TemplateSection {
+                id: section.id.clone(),
+                title: section.title.clone(),
+                content,
+                reference: None,
+            }
+        })
+        .collect();
+    let text = format::format_sections(&sections, profile);
+
+    RenderedTemplate { sections, text }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date_field(format: &str) -> FormField {
+        FormField {
+            name: "日期".to_string(),
+            field_type: FieldType::Date,
+            unit: None,
+            options: Vec::new(),
+            format: Some(format.to_string()),
+            default: None,
+        }
+    }
+
+    #[test]
+    fn date_format_rejects_time_and_timezone() {
+        assert!(is_valid_date_format("%Y年%m月%d日"));
+        assert!(is_valid_date_format("%Y-%m-%d %A"));
+        assert!(!is_valid_date_format("%Y-%m-%d %H:%M"));
+        assert!(!is_valid_date_format("%Y %z"));
+        assert!(!is_valid_date_format("%c"));
+        assert!(!is_valid_date_format("%Q"));
+    }
+
+    #[test]
+    fn format_value_falls_back_on_format_error() {
+        assert_eq!(
+            format_value(&date_field("%Y年%m月%d日"), "2024-03-05"),
+            "2024年03月05日"
+        );
+        assert_eq!(
+            format_value(&date_field("%H时"), "2024-03-05"),
+            "2024-03-05"
+        );
+    }
+
+    #[test]
+    fn parse_field_keeps_colons_in_date_format() {
+        let field = parse_field("日期:date:%Y-%m-%d %H:%M");
+        assert_eq!(field.field_type, FieldType::Date);
+        // 含有时间说明符，格式被丢弃而不是被截断成 `%Y-%m-%d %H`
+        assert_eq!(field.format, None);
+        assert_eq!(field.default, None);
+
+        let field = parse_field("日期:date:%Y:%m:%d:2024-03-05");
+        assert_eq!(field.format.as_deref(), Some("%Y:%m:%d"));
+        assert_eq!(field.default.as_deref(), Some("2024-03-05"));
+
+        let field = parse_field("日期:date:%Y:%m:%d");
+        assert_eq!(field.format.as_deref(), Some("%Y:%m:%d"));
+        assert_eq!(field.default, None);
+    }
+
+    #[test]
+    fn parse_field_reads_argument_and_default() {
+        let field = parse_field("体温:number:℃:36.5");
+        assert_eq!(field.unit.as_deref(), Some("℃"));
+        assert_eq!(field.default.as_deref(), Some("36.5"));
+
+        let field = parse_field("时间:::08:00");
+        assert_eq!(field.field_type, FieldType::Text);
+        assert_eq!(field.default.as_deref(), Some("08:00"));
+    }
+
+    #[test]
+    fn block_keywords_need_a_separator() {
+        assert!(syntax_errors("{{#if 体温 > 37}}发热{{else if\t体温 > 36}}低热{{/if}}").is_empty());
+        assert_eq!(syntax_errors("{{#iffy}}").len(), 1);
+        assert_eq!(syntax_errors("{{#if体温>37}}发热{{/if}}").len(), 2);
+        // `elseif` 不是分支标签，按占位符处理
+        let (nodes, errors) = parse("{{#if 1}}a{{elseif}}b{{/if}}");
+        assert!(errors.is_empty());
+        assert_eq!(nodes.len(), 1);
+    }
+
+    #[test]
+    fn renders_placeholders_and_conditions() {
+        let template = Template::for_test(
+            "入院记录",
+            vec![TemplateSection::for_test(
+                "现病史",
+                "{{体温:number:℃}}，{{#if 体温 > 37.3}}发热{{else}}无发热{{/if}}，{{咳嗽:select:有|无:无}}咳嗽，{{未填写}}",
+            )],
+        );
+        let values = HashMap::from([("体温".to_string(), "38.5".to_string())]);
+        let rendered = render_template(&template, &values, &FormatProfile::content_only());
+        assert_eq!(rendered.text, "38.5℃，发热，无咳嗽，{{未填写}}");
+
+        let errors = validate_values(
+            &form_schema(&template),
+            &HashMap::from([
+                ("体温".to_string(), "高".to_string()),
+                ("咳嗽".to_string(), "偶尔".to_string()),
+            ]),
+        );
+        let fields: Vec<&str> = errors.iter().map(|error| error.field.as_str()).collect();
+        assert_eq!(fields, ["体温", "咳嗽"]);
+    }
+
+    #[test]
+    fn take_cursor_reports_offset_from_end() {
+        assert_eq!(
+            take_cursor("主诉：{{cursor}}5天"),
+            ("主诉：5天".to_string(), Some(2))
+        );
+        assert_eq!(take_cursor("无标记"), ("无标记".to_string(), None));
+        assert_eq!(take_cursor("末尾{{cursor}}"), ("末尾".to_string(), Some(0)));
+        // 以第一个标记为准，其余标记被去除
+        assert_eq!(
+            take_cursor("a{{cursor}}b{{cursor}}c"),
+            ("abc".to_string(), Some(2))
+        );
+        // `\r\n` 计为一个字符
+        assert_eq!(
+            take_cursor("a{{cursor}}b\r\nc"),
+            ("ab\r\nc".to_string(), Some(3))
+        );
+    }
+}
@@ -0,0 +1,299 @@
+use crate::database::Template;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::sync::Mutex;
+
+/// 当前使用的推荐规则，在加载和保存规则时编译
+static RULES: Mutex<Option<CompiledRules>> = Mutex::new(None);
+
+/// 根据目标窗口标题推荐模板的规则
+///
+/// 模板类型、疾病和标签可以用 `$1`、`${name}` 引用正则中的捕获组，
+/// 例如从 `(.+)入院记录` 中取出疾病名称
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SuggestionRule {
+    /// 匹配窗口标题的正则表达式
+    pub pattern: String,
+    /// 推荐的模板类型，为空时不限
+    pub template_type: String,
+    /// 推荐的疾病，为空时不限
+    pub disease: String,
+    /// 推荐的标签名称，与模板 `tags` 中的标签名称比较，为空时不限
+    pub tag: String,
+    /// 命中时模板增加的得分
+    pub weight: u32,
+}
+
+impl Default for SuggestionRule {
+    fn default() -> Self {
+        SuggestionRule {
+            pattern: String::new(),
+            template_type: String::new(),
+            disease: String::new(),
+            tag: String::new(),
+            weight: 1,
+        }
+    }
+}
+
+/// 规则匹配窗口标题后得到的筛选条件
+struct RuleMatch {
+    template_type: String,
+    disease: String,
+    tag: String,
+    weight: u32,
+}
+
+impl RuleMatch {
+    /// 所有非空条件都满足时命中模板
+    fn hits(&self, template: &Template) -> bool {
+        (self.template_type.is_empty() || template.template_type == self.template_type)
+            && (self.disease.is_empty() || template.disease == self.disease)
+            && (self.tag.is_empty() || template.tags.contains(&self.tag))
+    }
+}
+
+/// 推荐的模板及其得分
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateSuggestion {
+    pub template: Template,
+    pub score: u32,
+}
+
+/// 模板推荐结果
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TemplateSuggestions {
+    /// 用于匹配的窗口标题，没有目标窗口时为空
+    pub window_title: String,
+    /// 命中规则给出的筛选条件，可直接用于模板列表的筛选
+    pub template_types: Vec<String>,
+    pub diseases: Vec<String>,
+    pub tags: Vec<String>,
+    /// 按得分从高到低排列，同分时收藏的和最近修改的在前
+    pub suggestions: Vec<TemplateSuggestion>,
+}
+
+/// 编译好正则表达式的推荐规则
+#[derive(Debug, Default)]
+pub struct CompiledRules {
+    rules: Vec<(Regex, SuggestionRule)>,
+}
+
+impl CompiledRules {
+    /// 编译规则中的正则表达式，返回第一个错误
+    pub fn compile(rules: &[SuggestionRule]) -> Result<Self, String> {
+        let rules = rules
+            .iter()
+            .map(|rule| {
+                if rule.pattern.trim().is_empty() {
+                    return Err("Suggestion rule pattern is empty".to_string());
+                }
+                let regex = Regex::new(&rule.pattern).map_err(|e| {
+                    format!("Invalid suggestion rule pattern {}: {}", rule.pattern, e)
+                })?;
+                Ok((regex, rule.clone()))
+            })
+            .collect::<Result<_, String>>()?;
+        Ok(CompiledRules { rules })
+    }
+}
+
+/// 更新当前使用的推荐规则
+pub fn set_rules(rules: CompiledRules) {
+    if let Ok(mut current) = RULES.lock() {
+        *current = Some(rules);
+    }
+}
+
+// 用窗口标题匹配规则，并展开筛选条件中的捕获组引用
+fn match_rules(rules: &CompiledRules, title: &str) -> Vec<RuleMatch> {
+    rules
+        .rules
+        .iter()
+        .filter_map(|(regex, rule)| {
+            let captures = regex.captures(title)?;
+            let expand = |replacement: &str| {
+                let mut expanded = String::new();
+                captures.expand(replacement, &mut expanded);
+                expanded.trim().to_string()
+            };
+            Some(RuleMatch {
+                template_type: expand(&rule.template_type),
+                disease: expand(&rule.disease),
+                tag: expand(&rule.tag),
+                weight: rule.weight,
+            })
+        })
+        .filter(|matched| {
+            !(matched.template_type.is_empty()
+                && matched.disease.is_empty()
+                && matched.tag.is_empty())
+        })
+        .collect()
+}
+
+fn push_unique(values: &mut Vec<String>, value: &str) {
+    if !value.is_empty() && !values.iter().any(|existing| existing == value) {
+        values.push(value.to_string());
+    }
+}
+
+/// 用当前的推荐规则根据窗口标题推荐模板，规则尚未加载时不推荐任何模板
+pub fn suggest(window_title: &str, templates: Vec<Template>) -> TemplateSuggestions {
+    match RULES.lock() {
+        Ok(rules) => suggest_with(
+            rules.as_ref().unwrap_or(&CompiledRules::default()),
+            window_title,
+            templates,
+        ),
+        Err(_) => suggest_with(&CompiledRules::default(), window_title, templates),
+    }
+}
+
+/// 根据窗口标题推荐模板，只返回至少命中一条规则的模板
+fn suggest_with(
+    rules: &CompiledRules,
+    window_title: &str,
+    templates: Vec<Template>,
+) -> TemplateSuggestions {
+    let matches = match_rules(rules, window_title);
+
+    let mut result = TemplateSuggestions {
+        window_title: window_title.to_string(),
+        ..Default::default()
+    };
+    for matched in &matches {
+        push_unique(&mut result.template_types, &matched.template_type);
+        push_unique(&mut result.diseases, &matched.disease);
+        push_unique(&mut result.tags, &matched.tag);
+    }
+
+    result.suggestions = templates
+        .into_iter()
+        .filter_map(|template| {
+            let score: u32 = matches
+                .iter()
+                .filter(|matched| matched.hits(&template))
+                .map(|matched| matched.weight)
+                .sum();
+            (score > 0).then_some(TemplateSuggestion { template, score })
+        })
+        .collect();
+    result.suggestions.sort_by_key(|suggestion| {
+        (
+            Reverse(suggestion.score),
+            Reverse(suggestion.template.is_favorite),
+            Reverse(suggestion.template.updated_at),
+        )
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(id: &str, template_type: &str, disease: &str, tags: &[&str]) -> Template {
+        Template {
+            id: id.to_string(),
+            disease: disease.to_string(),
+            template_type: template_type.to_string(),
+            tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            ..Template::for_test(id, Vec::new())
+        }
+    }
+
+    fn rule(pattern: &str, template_type: &str, disease: &str, weight: u32) -> SuggestionRule {
+        SuggestionRule {
+            pattern: pattern.to_string(),
+            template_type: template_type.to_string(),
+            disease: disease.to_string(),
+            weight,
+            ..Default::default()
+        }
+    }
+
+    fn suggest(
+        rules: &[SuggestionRule],
+        window_title: &str,
+        templates: Vec<Template>,
+    ) -> TemplateSuggestions {
+        suggest_with(
+            &CompiledRules::compile(rules).unwrap(),
+            window_title,
+            templates,
+        )
+    }
+
+    fn ids(suggestions: &TemplateSuggestions) -> Vec<&str> {
+        suggestions
+            .suggestions
+            .iter()
+            .map(|suggestion| suggestion.template.id.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn expands_capture_groups() {
+        let rules = [rule(
+            r"(?<disease>\S+)入院记录",
+            "入院记录",
+            "${disease}",
+            1,
+        )];
+        let templates = vec![
+            template("pneumonia", "入院记录", "肺炎", &[]),
+            template("other-disease", "入院记录", "哮喘", &[]),
+            template("other-type", "出院记录", "肺炎", &[]),
+        ];
+        let result = suggest(&rules, "张三 肺炎入院记录 - 电子病历", templates);
+        assert_eq!(result.template_types, ["入院记录"]);
+        assert_eq!(result.diseases, ["肺炎"]);
+        assert_eq!(ids(&result), ["pneumonia"]);
+    }
+
+    #[test]
+    fn sums_weights_and_orders_by_score() {
+        let rules = [
+            rule("病程", "病程记录", "", 1),
+            SuggestionRule {
+                pattern: "病程".to_string(),
+                tag: "常用".to_string(),
+                weight: 3,
+                ..Default::default()
+            },
+        ];
+        let mut favorite = template("favorite", "病程记录", "", &[]);
+        favorite.is_favorite = true;
+        let templates = vec![
+            template("type-only", "病程记录", "", &[]),
+            favorite,
+            template("tagged", "病程记录", "", &["常用"]),
+            template("unrelated", "出院记录", "", &[]),
+        ];
+        let result = suggest(&rules, "日常病程记录", templates);
+        assert_eq!(ids(&result), ["tagged", "favorite", "type-only"]);
+        assert_eq!(result.suggestions[0].score, 4);
+    }
+
+    #[test]
+    fn ignores_unmatched_and_empty_rules() {
+        let rules = [rule("手术", "手术记录", "", 1), rule("病历", "", "", 1)];
+        let templates = vec![template("surgery", "手术记录", "", &[])];
+        let result = suggest(&rules, "门诊病历", templates);
+        assert!(result.suggestions.is_empty());
+        assert!(result.template_types.is_empty());
+        assert_eq!(result.window_title, "门诊病历");
+    }
+
+    #[test]
+    fn validates_patterns() {
+        assert!(CompiledRules::compile(&[rule("入院", "", "", 1)]).is_ok());
+        assert!(CompiledRules::compile(&[rule("  ", "", "", 1)]).is_err());
+        assert!(CompiledRules::compile(&[rule("(未闭合", "", "", 1)]).is_err());
+    }
+}
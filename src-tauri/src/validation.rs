@@ -0,0 +1,360 @@
+use crate::database::{Snippet, Template};
+use crate::render;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// 未填写疾病或模板类型时使用的默认分类
+pub const DEFAULT_CATEGORY: &str = "未分类";
+
+/// 模板校验规则
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ValidationRules {
+    pub max_title_length: usize,
+    pub max_sections: usize,
+    pub max_section_title_length: usize,
+    pub max_section_content_length: usize,
+    pub max_category_length: usize,
+    pub max_tags: usize,
+    pub max_tag_length: usize,
+    /// 章节标题不能重复
+    pub unique_section_titles: bool,
+    /// 禁止出现换行、制表符以外的控制字符
+    pub forbid_control_chars: bool,
+    /// 检查条件块、计算表达式等模板语法
+    pub check_template_syntax: bool,
+}
+
+impl Default for ValidationRules {
+    fn default() -> Self {
+        ValidationRules {
+            max_title_length: 100,
+            max_sections: 50,
+            max_section_title_length: 50,
+            max_section_content_length: 20_000,
+            max_category_length: 50,
+            max_tags: 20,
+            max_tag_length: 30,
+            unique_section_titles: true,
+            forbid_control_chars: true,
+            check_template_syntax: true,
+        }
+    }
+}
+
+/// 字段级校验错误，`field` 为字段路径，例如 `sections[1].title`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FieldError {
+    pub field: String,
+    pub message: String,
+}
+
+impl FieldError {
+    fn new(field: impl Into<String>, message: impl Into<String>) -> Self {
+        FieldError {
+            field: field.into(),
+            message: message.into(),
+        }
+    }
+
+    /// 为字段路径添加前缀，用于批量导入时标明是第几个模板
+    pub fn prefixed(self, prefix: &str) -> Self {
+        FieldError {
+            field: format!("{}.{}", prefix, self.field),
+            message: self.message,
+        }
+    }
+}
+
+/// 规范化模板：去除标题首尾空白，空的疾病和模板类型使用默认分类
+pub fn normalize_template(template: &mut Template) {
+    template.title = template.title.trim().to_string();
+    for section in &mut template.sections {
+        section.title = section.title.trim().to_string();
+    }
+    template.disease = normalize_category(&template.disease);
+    template.template_type = normalize_category(&template.template_type);
+}
+
+/// 规范化疾病或模板类型：去除首尾空白，为空时使用默认分类
+pub fn normalize_category(category: &str) -> String {
+    match category.trim() {
+        "" => DEFAULT_CATEGORY.to_string(),
+        category => category.to_string(),
+    }
+}
+
+/// 按规则校验模板，返回所有字段错误
+pub fn validate_template(template: &Template, rules: &ValidationRules) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+
+    check_text(
+        &mut errors,
+        "title",
+        &template.title,
+        rules.max_title_length,
+        rules,
+        false,
+    );
+    if template.title.trim().is_empty() {
+        errors.push(FieldError::new("title", "标题不能为空"));
+    }
+
+    errors.extend(validate_category("disease", &template.disease, rules));
+    errors.extend(validate_category(
+        "templateType",
+        &template.template_type,
+        rules,
+    ));
+
+    if template.sections.len() > rules.max_sections {
+        errors.push(FieldError::new(
+            "sections",
+            format!("章节数量不能超过 {} 个", rules.max_sections),
+        ));
+    }
+    if !template
+        .sections
+        .iter()
+        .any(|section| !section.content.trim().is_empty() || section.reference.is_some())
+    {
+        errors.push(FieldError::new("sections", "至少需要一个有内容的章节"));
+    }
+
+    let mut section_titles = HashSet::new();
+    for (index, section) in template.sections.iter().enumerate() {
+        let title_field = format!("sections[{}].title", index);
+        if section.title.trim().is_empty() {
+            errors.push(FieldError::new(&title_field, "章节标题不能为空"));
+        } else if rules.unique_section_titles && !section_titles.insert(section.title.trim()) {
+            errors.push(FieldError::new(&title_field, "章节标题重复"));
+        }
+        check_text(
+            &mut errors,
+            &title_field,
+            &section.title,
+            rules.max_section_title_length,
+            rules,
+            false,
+        );
+        let content_field = format!("sections[{}].content", index);
+        check_text(
+            &mut errors,
+            &content_field,
+            &section.content,
+            rules.max_section_content_length,
+            rules,
+            true,
+        );
+        if rules.check_template_syntax {
+            for message in render::syntax_errors(&section.content) {
+                errors.push(FieldError::new(&content_field, message));
+            }
+        }
+    }
+
+    errors.extend(validate_tags(&template.tags, rules));
+    errors
+}
+
+/// 校验疾病或模板类型，`field` 为错误中使用的字段路径
+pub fn validate_category(field: &str, category: &str, rules: &ValidationRules) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    check_text(
+        &mut errors,
+        field,
+        category,
+        rules.max_category_length,
+        rules,
+        false,
+    );
+    errors
+}
+
+/// 校验标签列表：数量、空标签、长度和控制字符
+pub fn validate_tags(tags: &[String], rules: &ValidationRules) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if tags.len() > rules.max_tags {
+        errors.push(FieldError::new(
+            "tags",
+            format!("标签数量不能超过 {} 个", rules.max_tags),
+        ));
+    }
+    for (index, tag) in tags.iter().enumerate() {
+        let field = format!("tags[{}]", index);
+        if tag.trim().is_empty() {
+            errors.push(FieldError::new(&field, "标签不能为空"));
+        }
+        check_text(&mut errors, &field, tag, rules.max_tag_length, rules, false);
+    }
+    errors
+}
+
+/// 校验常用短语：内容不能为空，长度限制与章节内容相同
+pub fn validate_snippet(snippet: &Snippet, rules: &ValidationRules) -> Vec<FieldError> {
+    let mut errors = Vec::new();
+    if snippet.content.trim().is_empty() {
+        errors.push(FieldError::new("content", "内容不能为空"));
+    }
+    check_text(
+        &mut errors,
+        "content",
+        &snippet.content,
+        rules.max_section_content_length,
+        rules,
+        true,
+    );
+    errors.extend(validate_category("category", &snippet.category, rules));
+    errors
+}
+
+/// 校验文本长度和控制字符，`multiline` 为真时允许换行和制表符
+fn check_text(
+    errors: &mut Vec<FieldError>,
+    field: &str,
+    text: &str,
+    max_length: usize,
+    rules: &ValidationRules,
+    multiline: bool,
+) {
+    let length = text.chars().count();
+    if length > max_length {
+        errors.push(FieldError::new(
+            field,
+            format!("长度不能超过 {} 个字符（当前 {} 个）", max_length, length),
+        ));
+    }
+
+    if rules.forbid_control_chars {
+        let allowed = |c: char| multiline && matches!(c, '\n' | '\r' | '\t');
+        if text.chars().any(|c| c.is_control() && !allowed(c)) {
+            errors.push(FieldError::new(field, "包含不允许的控制字符"));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::database::TemplateSection;
+
+    fn section(title: &str, content: &str) -> TemplateSection {
+        TemplateSection::for_test(title, content)
+    }
+
+    fn fields(errors: &[FieldError]) -> Vec<&str> {
+        errors.iter().map(|error| error.field.as_str()).collect()
+    }
+
+    #[test]
+    fn normalize_fills_default_categories() {
+        let mut template = Template::for_test("  入院记录 ", vec![section(" 主诉 ", "发热")]);
+        normalize_template(&mut template);
+        assert_eq!(template.title, "入院记录");
+        assert_eq!(template.sections[0].title, "主诉");
+        assert_eq!(template.disease, DEFAULT_CATEGORY);
+        assert_eq!(template.template_type, DEFAULT_CATEGORY);
+    }
+
+    #[test]
+    fn valid_template_has_no_errors() {
+        let template = Template::for_test(
+            "入院记录",
+            vec![section("主诉", "发热3天"), section("现病史", "无\n\t")],
+        );
+        assert!(validate_template(&template, &ValidationRules::default()).is_empty());
+    }
+
+    #[test]
+    fn reports_field_paths() {
+        let mut template = Template::for_test(
+            "",
+            vec![
+                section("主诉", ""),
+                section("主诉", "含有\u{7}响铃"),
+                section("现病史", "{{#if 体温 > 37}}发热"),
+            ],
+        );
+        template.tags = vec![" ".to_string()];
+        let errors = validate_template(&template, &ValidationRules::default());
+        assert_eq!(
+            fields(&errors),
+            [
+                "title",
+                "sections[1].title",
+                "sections[1].content",
+                "sections[2].content",
+                "tags[0]",
+            ]
+        );
+    }
+
+    #[test]
+    fn rules_can_be_relaxed() {
+        let rules = ValidationRules {
+            max_title_length: 2,
+            unique_section_titles: false,
+            forbid_control_chars: false,
+            check_template_syntax: false,
+            ..Default::default()
+        };
+        let template = Template::for_test(
+            "入院记录",
+            vec![section("主诉", "\u{7}"), section("主诉", "{{#if")],
+        );
+        assert_eq!(fields(&validate_template(&template, &rules)), ["title"]);
+    }
+
+    #[test]
+    fn requires_a_section_with_content() {
+        let template = Template::for_test("入院记录", vec![section("主诉", "  ")]);
+        assert_eq!(
+            fields(&validate_template(&template, &ValidationRules::default())),
+            ["sections"]
+        );
+    }
+
+    #[test]
+    fn bulk_values_follow_template_rules() {
+        assert_eq!(normalize_category("  "), DEFAULT_CATEGORY);
+        assert_eq!(normalize_category(" 肺炎 "), "肺炎");
+        let rules = ValidationRules::default();
+        let long = "字".repeat(rules.max_category_length + 1);
+        assert_eq!(
+            fields(&validate_category("disease", &long, &rules)),
+            ["disease"]
+        );
+        let tags = vec!["常用".to_string(), "".to_string()];
+        assert_eq!(fields(&validate_tags(&tags, &rules)), ["tags[1]"]);
+    }
+
+    #[test]
+    fn validates_snippets() {
+        let snippet = |content: &str| Snippet {
+            id: String::new(),
+            content: content.to_string(),
+            category: String::new(),
+            pinyin: String::new(),
+            usage_count: 0,
+            created_at: 0,
+            updated_at: 0,
+        };
+        let rules = ValidationRules::default();
+        assert!(validate_snippet(&snippet("否认药物过敏史"), &rules).is_empty());
+        assert!(validate_snippet(&snippet("两行\n内容"), &rules).is_empty());
+        assert_eq!(
+            fields(&validate_snippet(&snippet(" "), &rules)),
+            ["content"]
+        );
+        assert_eq!(
+            fields(&validate_snippet(&snippet("\u{0}"), &rules)),
+            ["content"]
+        );
+        let long = "字".repeat(rules.max_section_content_length + 1);
+        assert_eq!(
+            fields(&validate_snippet(&snippet(&long), &rules)),
+            ["content"]
+        );
+    }
+}
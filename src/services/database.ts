@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core'
-import type { Template, Tag, DiseaseInfo, TemplateTypeInfo } from '../types'
+import type { Template, Tag, DiseaseInfo, TemplateTypeInfo, TemplateSuggestions } from '../types'
 
 /**
  * 数据库服务类，提供与Rust后端数据库交互的方法
@@ -44,12 +44,12 @@ export class DatabaseService {
   }
 
   /**
-   * 保存模板
+   * 保存模板，返回由后端分配ID和时间戳后的模板
    */
-  static async saveTemplate(template: Template): Promise<string> {
+  static async saveTemplate(template: Template): Promise<Template> {
     try {
       console.log('saveTemplate', template);
-      return await invoke<string>('save_template', { template })
+      return await invoke<Template>('save_template', { template })
 
     } catch (error) {
       console.error('Failed to save template:', error)
@@ -59,6 +59,8 @@ export class DatabaseService {
 
   /**
    * 删除模板
+   *
+   * 失败时抛出 DeleteError，章节仍被其他模板引用时 kind 为 referenced
    */
   static async deleteTemplate(id: string): Promise<string> {
     try {
@@ -93,6 +95,19 @@ export class DatabaseService {
     }
   }
 
+  /**
+   * 根据粘贴目标的窗口标题推荐模板
+   * @param targetWindow 目标窗口ID，不传时使用上一个窗口
+   */
+  static async suggestTemplates(targetWindow?: number): Promise<TemplateSuggestions> {
+    try {
+      return await invoke<TemplateSuggestions>('suggest_templates', { targetWindow })
+    } catch (error) {
+      console.error('Failed to suggest templates:', error)
+      throw error
+    }
+  }
+
   /**
    * 获取所有疾病分类
    */
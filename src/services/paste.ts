@@ -1,9 +1,93 @@
 import { invoke } from '@tauri-apps/api/core'
+import type { FieldPasteOptions, PasteError, PasteStrategy, PasteTargets, WindowInfo } from '../types'
 
-export async function paste(_text: string) {
-    invoke('paste').then((res) => {
-        console.log("paste", res)
-    }).catch((err) => {
-        console.log(err)
-    })
+/**
+ * 内置的只输出章节内容（不含标题、编号）的格式方案ID，用于单独粘贴一个章节
+ */
+export const CONTENT_ONLY_PROFILE_ID = 'contentOnly'
+
+/**
+ * 渲染模板中选中的章节并粘贴到上一个窗口
+ * @param templateId 模板ID
+ * @param sectionIndexes 按粘贴顺序排列的章节下标，为空时粘贴整个模板
+ * @param profileId 格式方案ID，不传时使用默认方案
+ * @param strategy 粘贴方式，不传时使用设置中的默认方式
+ * @param targetWindow 目标窗口ID，不传时粘贴到上一个窗口
+ * @throws PasteError 粘贴失败的原因
+ */
+export async function paste(templateId: string, sectionIndexes: number[], profileId?: string, strategy?: PasteStrategy, targetWindow?: number): Promise<void> {
+    try {
+        await invoke<void>('paste', { id: templateId, sectionIndexes, profileId, strategy, targetWindow })
+    } catch (error) {
+        console.error('Failed to paste:', error)
+        throw error as PasteError
+    }
+}
+
+/**
+ * 将给定文本粘贴到上一个窗口，粘贴后恢复原来的剪贴板内容
+ * @param text 要粘贴的文本，可包含 {{cursor}} 光标标记
+ * @param strategy 粘贴方式，不传时使用设置中的默认方式
+ * @param targetWindow 目标窗口ID，不传时粘贴到上一个窗口
+ * @throws PasteError 粘贴失败的原因
+ */
+export async function pasteText(text: string, strategy?: PasteStrategy, targetWindow?: number): Promise<void> {
+    try {
+        await invoke<void>('paste_text', { text, strategy, targetWindow })
+    } catch (error) {
+        console.error('Failed to paste text:', error)
+        throw error as PasteError
+    }
+}
+
+/**
+ * 将模板中选中的章节逐个粘贴到上一个窗口的各个输入框，章节之间发送导航按键
+ * @param templateId 模板ID
+ * @param sectionIndexes 按粘贴顺序排列的章节下标，为空时粘贴所有章节
+ * @param options 导航按键和延时设置，不传时使用默认设置（Tab 切换）
+ * @param strategy 粘贴方式，不传时使用设置中的默认方式
+ * @param targetWindow 目标窗口ID，不传时粘贴到上一个窗口
+ * @throws PasteError 粘贴失败的原因
+ */
+export async function pasteFields(templateId: string, sectionIndexes: number[], options?: FieldPasteOptions, strategy?: PasteStrategy, targetWindow?: number): Promise<void> {
+    try {
+        await invoke<void>('paste_fields', { id: templateId, sectionIndexes, options, strategy, targetWindow })
+    } catch (error) {
+        console.error('Failed to paste fields:', error)
+        throw error as PasteError
+    }
+}
+
+/**
+ * 把粘贴失败的原因转换为给用户看的提示
+ */
+export function pasteErrorMessage(error: PasteError): string {
+    switch (error.kind) {
+        case 'noPreviousWindow':
+            return '没有可以粘贴的目标窗口，请先切换到要粘贴的窗口'
+        case 'focusFailed':
+            return `无法切换到目标窗口：${error.message}`
+        case 'backendUnavailable':
+            return `当前环境无法模拟输入：${error.message}`
+        case 'keySendFailed':
+            return `发送粘贴按键失败：${error.message}`
+        case 'clipboardFailed':
+            return `读写剪贴板失败：${error.message}`
+        case 'failed':
+            return `粘贴失败：${error.message}`
+    }
+}
+
+/**
+ * 获取最近的前台窗口，第一个即为粘贴的目标
+ */
+export async function getWindowHistory(): Promise<WindowInfo[]> {
+    return await invoke<WindowInfo[]>('get_window_history')
+}
+
+/**
+ * 列出可以作为粘贴目标的窗口，以及本次运行中最后一次选择的目标
+ */
+export async function getPasteTargets(): Promise<PasteTargets> {
+    return await invoke<PasteTargets>('get_paste_targets')
 }
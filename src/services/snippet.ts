@@ -0,0 +1,96 @@
+import { invoke } from '@tauri-apps/api/core'
+import type { Snippet, SnippetCategory } from '../types'
+import { getFullPinyin, getPinyinInitials } from '../utils/pinyin'
+
+/**
+ * 生成常用短语的拼音检索词：全拼和首字母，以空格分隔
+ */
+export function snippetPinyin(content: string): string {
+  return [getFullPinyin(content), getPinyinInitials(content)]
+    .filter((part) => part)
+    .join(' ')
+}
+
+/**
+ * 常用短语服务类，保存前在前端生成拼音检索词
+ */
+export class SnippetService {
+  /**
+   * 获取所有常用短语，按使用次数排序
+   */
+  static async getAllSnippets(): Promise<Snippet[]> {
+    try {
+      return await invoke<Snippet[]>('get_all_snippets')
+    } catch (error) {
+      console.error('Failed to get all snippets:', error)
+      throw error
+    }
+  }
+
+  /**
+   * 按内容或拼音搜索常用短语
+   * @param category 分类，不传时搜索全部分类
+   */
+  static async searchSnippets(keyword: string, category?: string): Promise<Snippet[]> {
+    try {
+      return await invoke<Snippet[]>('search_snippets', { keyword, category })
+    } catch (error) {
+      console.error('Failed to search snippets:', error)
+      throw error
+    }
+  }
+
+  /**
+   * 获取所有常用短语分类及其短语数量
+   */
+  static async getAllSnippetCategories(): Promise<SnippetCategory[]> {
+    try {
+      return await invoke<SnippetCategory[]>('get_all_snippet_categories')
+    } catch (error) {
+      console.error('Failed to get snippet categories:', error)
+      throw error
+    }
+  }
+
+  /**
+   * 保存常用短语，返回由后端分配ID和时间戳后的短语
+   *
+   * 失败时抛出 SaveError，内容校验失败时 kind 为 validation
+   */
+  static async saveSnippet(snippet: Snippet): Promise<Snippet> {
+    try {
+      return await invoke<Snippet>('save_snippet', {
+        snippet: { ...snippet, pinyin: snippetPinyin(snippet.content) }
+      })
+    } catch (error) {
+      console.error('Failed to save snippet:', error)
+      throw error
+    }
+  }
+
+  /**
+   * 删除常用短语
+   *
+   * 失败时抛出 DeleteError，仍被模板引用时 kind 为 referenced
+   */
+  static async deleteSnippet(id: string): Promise<string> {
+    try {
+      return await invoke<string>('delete_snippet', { id })
+    } catch (error) {
+      console.error('Failed to delete snippet:', error)
+      throw error
+    }
+  }
+
+  /**
+   * 记录一次使用，用于排序
+   */
+  static async recordSnippetUsage(id: string): Promise<string> {
+    try {
+      return await invoke<string>('record_snippet_usage', { id })
+    } catch (error) {
+      console.error('Failed to record snippet usage:', error)
+      throw error
+    }
+  }
+}
@@ -236,14 +236,14 @@ export const useTemplateStore = defineStore('template', () => {
      */
     const updateTemplate = async (template: Template) => {
         try {
-            await DatabaseService.saveTemplate(template)
+            const savedTemplate = await DatabaseService.saveTemplate(template)
             // 重新加载模板数据以反映更改
             const templatesData = await DatabaseService.getAllTemplates()
             templates.value = templatesData
 
             // 更新当前选中的模板
             if (selectedTemplate.value && selectedTemplate.value.id === template.id) {
-                selectedTemplate.value = template
+                selectedTemplate.value = savedTemplate
             }
         } catch (error) {
             console.error('Failed to update template:', error)
@@ -259,7 +259,7 @@ export const useTemplateStore = defineStore('template', () => {
             // 创建新模板的完整数据
             // todo!
             const newTemplate: Template = {
-                id: `template_${Date.now()}`,
+                id: '',
                 title: templateData.title || '新建模板',
                 sections: templateData.sections || [
                     {
@@ -287,16 +287,16 @@ export const useTemplateStore = defineStore('template', () => {
             }
 
             // 保存到数据库
-            await DatabaseService.saveTemplate(newTemplate)
+            const savedTemplate = await DatabaseService.saveTemplate(newTemplate)
 
             // 重新加载模板数据
             const templatesData = await DatabaseService.getAllTemplates()
             templates.value = templatesData
 
             // 选择新创建的模板
-            selectedTemplate.value = newTemplate
+            selectedTemplate.value = savedTemplate
 
-            return newTemplate
+            return savedTemplate
         } catch (error) {
             console.error('Failed to create template:', error)
             throw error
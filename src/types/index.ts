@@ -13,11 +13,17 @@ export interface Tag {
   templateCount?: number; // 模板数量
 }
 
+// 章节引用
+export type SectionReference =
+  | { kind: 'section'; templateId: TemplateID; sectionId: string } // 引用其他模板的章节
+  | { kind: 'snippet'; snippetId: string }; // 引用常用短语
+
 // 模板内容部分
 export interface TemplateSection {
-  id?: string; // 可选的ID字段
+  id?: string; // 章节ID，保存时由后端分配
   title: string;
   content: string;
+  reference?: SectionReference; // 设置后使用被引用的内容
 }
 
 // 病历模板
@@ -31,6 +37,147 @@ export interface Template {
   createdAt: Timestamp;
   updatedAt: Timestamp;
   isFavorite: boolean;
+  version?: number; // 版本号，由后端维护，用于检测并发修改
+}
+
+// 字段级校验错误，field 为字段路径，例如 sections[1].title
+export interface FieldError {
+  field: string;
+  message: string;
+}
+
+// 保存模板或常用短语失败的原因
+export type SaveError =
+  | { kind: 'conflict'; current: Template } // 模板已被其他窗口修改
+  | { kind: 'validation'; errors: FieldError[] } // 内容未通过校验
+  | { kind: 'database'; message: string };
+
+// 引用了某个章节或常用短语的章节
+export interface SectionDependent {
+  templateId: string;
+  templateTitle: string;
+  sectionId: string;
+  sectionTitle: string;
+  depth: number; // 引用层级，1 表示直接引用
+}
+
+// 删除模板或常用短语失败的原因
+export type DeleteError =
+  | { kind: 'referenced'; dependents: SectionDependent[] } // 仍被其他模板的章节引用
+  | { kind: 'database'; message: string };
+
+// 常用短语
+export interface Snippet {
+  id: string;
+  content: string;
+  category: string;
+  pinyin: string; // 拼音检索词（全拼和首字母，以空格分隔）
+  usageCount: number;
+  createdAt: Timestamp;
+  updatedAt: Timestamp;
+}
+
+// 常用短语分类
+export interface SnippetCategory {
+  name: string;
+  snippetCount: number;
+}
+
+// 文本输出的格式方案
+export interface FormatProfile {
+  id: string; // 新建时留空，由后端分配
+  name: string;
+  titleLayout: 'inline' | 'ownLine' | 'hidden'; // 标题与内容同行、单独一行或不输出
+  numbering: 'none' | 'arabic' | 'chinese'; // 章节编号：无、1. 或 一、
+  fullWidthColon: boolean;
+  indent: number; // 内容缩进的空格数，最多 16 个
+  blankLineBetweenSections: boolean;
+  skipEmptySections: boolean;
+}
+
+// 把文本送入目标窗口的方式
+export type PasteStrategy =
+  | { kind: 'shiftInsert' } // 写入剪贴板后发送 Shift+Insert
+  | { kind: 'ctrlV' } // 写入剪贴板后发送 Ctrl+V
+  | { kind: 'type'; delay?: number }; // 逐字模拟键盘输入，delay 为字符间隔（毫秒）
+
+// 针对某类目标窗口的粘贴方案，按目标窗口的进程名或标题匹配，排在前面的优先
+export interface AppPasteProfile {
+  id: string; // 新建时留空，由后端分配
+  name: string;
+  processPattern: string; // 进程名模式，支持 * 和 ? 通配符，不区分大小写，为空时不按进程名匹配
+  titlePattern: string; // 窗口标题模式，为空时不按标题匹配
+  strategy: PasteStrategy;
+  focusDelay: number; // 切换到目标窗口后等待多久再粘贴（毫秒）
+  restoreDelay: number; // 粘贴后等待多久再恢复剪贴板（毫秒）
+  formatProfileId?: string; // 使用的格式方案，不设置时使用默认方案
+  newline: 'lf' | 'crlf' | 'space'; // 换行符：\n、\r\n 或替换为空格
+}
+
+// 曾经处于前台的窗口
+export interface WindowInfo {
+  id: number;
+  title: string;
+  processId: number;
+  processName: string; // 可执行文件名，无法获取时为空
+  iconId: string; // 图标标识，同一程序的窗口相同（Windows 上为可执行文件路径，X11 上为 WM_CLASS）
+  activatedAt: Timestamp; // 最近一次切换到前台的时间，未记录过时为 0
+}
+
+// 可选的粘贴目标
+export interface PasteTargets {
+  windows: WindowInfo[]; // 当前的顶层窗口，按前后顺序排列
+  lastTarget?: number; // 本次运行中最后一次选择的目标窗口ID
+}
+
+// 根据目标窗口标题推荐模板的规则，templateType、disease、tag 可用 $1、${name} 引用捕获组
+export interface SuggestionRule {
+  pattern: string; // 匹配窗口标题的正则表达式
+  templateType?: string; // 为空时不限
+  disease?: string;
+  tag?: string; // 标签名称，与模板 tags 中的名称比较
+  weight?: number; // 命中时模板增加的得分，默认为 1
+}
+
+// 模板推荐结果
+export interface TemplateSuggestions {
+  windowTitle: string; // 用于匹配的窗口标题，没有目标窗口时为空
+  templateTypes: string[]; // 命中规则给出的筛选条件
+  diseases: string[];
+  tags: string[];
+  suggestions: { template: Template; score: number }[]; // 按得分从高到低排列
+}
+
+// 悬浮窗口的状态，按显示器布局分别保存
+export interface FloatWindowState {
+  geometry?: { x: number; y: number; width: number; height: number }; // 物理像素，未保存过时为空
+  pinned: boolean; // 置顶显示
+  opacity: number; // 不透明度，0.2 ~ 1
+  visible: boolean; // 上次是否可见，启动时据此决定是否显示
+}
+
+// 不记录为粘贴目标的窗口，本程序自己的窗口总是被排除
+export interface WindowExclusionRules {
+  processes: string[]; // 进程名模式，支持 * 和 ? 通配符，不区分大小写
+  titles: string[]; // 窗口标题模式
+  excludeUntitled: boolean; // 排除没有标题的窗口
+}
+
+// 粘贴失败的原因
+export type PasteError =
+  | { kind: 'noPreviousWindow' } // 还没有记录到可以粘贴的目标窗口
+  | { kind: 'focusFailed'; message: string } // 无法把目标窗口切换到前台
+  | { kind: 'backendUnavailable'; message: string } // 输入模拟或窗口系统不可用
+  | { kind: 'keySendFailed'; message: string }
+  | { kind: 'clipboardFailed'; message: string }
+  | { kind: 'failed'; message: string }; // 模板不存在、数据库错误等
+
+// 逐项粘贴的设置
+export interface FieldPasteOptions {
+  navigationKeys?: string[]; // 章节之间发送的按键，例如 ['Tab']、['Tab', 'Tab']、['Shift+Tab']
+  keyDelay?: number; // 相邻按键之间的间隔（毫秒）
+  sectionDelay?: number; // 粘贴章节前后的等待时间（毫秒）
+  skipEmptySections?: boolean;
 }
 
 // 病种类型
@@ -26,7 +26,7 @@ export async function copyToClipboard(template: Template) {
 export function defaultTemplateValue(): Template {
     const now = Date.now()
     return {
-        id: 'template-' + now, // 空ID，通常在保存时由数据库生成
+        id: '', // 空ID，保存时由后端生成
         title: '新建模板',
         sections: [
             {